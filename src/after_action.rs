@@ -0,0 +1,642 @@
+// keeps track of what every unit did in a match and reports on it afterwards
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::game;
+
+/// what a unit did over the match
+#[derive(Clone)]
+pub struct UnitStats {
+    id:             u64,
+    name:           String,
+    team:           game::Team,
+    damage_dealt:   u64,
+    damage_taken:   u64,
+    kills:          u64,
+    tiles_moved:    u64,
+    // how many times each attack or item was used, in the order they were first used
+    attacks:        Vec<(String, u64)>,
+    joined_round:   u64,
+    killed_round:   Option<u64>
+}
+
+impl UnitStats {
+    /// creates empty stats for a unit joining in `round`
+    fn new(id: u64, name: String, team: game::Team, round: u64) -> Self {
+        UnitStats {
+            id,
+            name,
+            team,
+            damage_dealt: 0,
+            damage_taken: 0,
+            kills: 0,
+            tiles_moved: 0,
+            attacks: Vec::new(),
+            joined_round: round,
+            killed_round: None
+        }
+    }
+
+    /// counts a use of an attack or item
+    fn used(&mut self, attack: &str) {
+        match self.attacks.iter_mut().find(|(name, _)| name == attack) {
+            Some((_, count)) => *count += 1,
+            None => self.attacks.push((attack.to_string(), 1))
+        }
+    }
+
+    /// returns how the unit's match ended
+    fn fate(&self) -> String {
+        match self.killed_round {
+            Some(round) => format!("killed in round {}", round),
+            None => "survived".to_string()
+        }
+    }
+}
+
+/// what happened during one unit's turn
+pub struct Turn {
+    round:      u64,
+    // `None` for things that happen between turns, like reinforcements arriving
+    actor:      Option<(String, game::Team)>,
+    entries:    Vec<String>
+}
+
+/// totals for one team
+pub struct TeamStats {
+    team:           game::Team,
+    units:          u64,
+    survivors:      u64,
+    damage_dealt:   u64,
+    damage_taken:   u64,
+    kills:          u64,
+    tiles_moved:    u64
+}
+
+/// the report on a finished match
+pub struct Report {
+    outcome:    Option<game::Outcome>,
+    rounds:     u64,
+    teams:      Vec<TeamStats>,
+    units:      Vec<UnitStats>,
+    timeline:   Vec<Turn>
+}
+
+/// the events of the match, kept in the same steps as the undo history so
+/// that anything taken back doesn't count
+struct Journal {
+    roster:     Vec<UnitStats>,
+    steps:      Vec<Vec<game::Event>>,
+    pending:    Vec<game::Event>,
+    undone:     Vec<Vec<game::Event>>
+}
+
+/// collects the game's events into the journal
+struct Recorder(Rc<RefCell<Journal>>);
+
+impl game::Subscriber for Recorder {
+    fn notify(&mut self, event: &game::Event) {
+        let mut journal = self.0.borrow_mut();
+        match event {
+            game::Event::Committed => {
+                let step = std::mem::take(&mut journal.pending);
+                journal.steps.push(step);
+                journal.undone.clear();
+            },
+            game::Event::Undone => {
+                journal.pending.clear();
+                if let Some(step) = journal.steps.pop() {
+                    journal.undone.push(step);
+                }
+            },
+            game::Event::Amended => {
+                let events = std::mem::take(&mut journal.pending);
+                match journal.steps.last_mut() {
+                    Some(step) => step.extend(events),
+                    None => journal.steps.push(events)
+                }
+                journal.undone.clear();
+            },
+            game::Event::Redone => {
+                journal.pending.clear();
+                if let Some(step) = journal.undone.pop() {
+                    journal.steps.push(step);
+                }
+            },
+            _ => journal.pending.push(event.clone())
+        }
+    }
+}
+
+/// keeps track of a match for the after-action report
+pub struct AfterAction {
+    journal: Rc<RefCell<Journal>>
+}
+
+impl AfterAction {
+    /// starts keeping track of a match fought by `units`
+    pub fn new(units: &game::Units) -> Self {
+        let roster = units.iter()
+            .map(|u| UnitStats::new(u.entity_id(), u.name(), u.team(), 0))
+            .collect();
+        AfterAction {
+            journal: Rc::new(RefCell::new(Journal {
+                roster,
+                steps: Vec::new(),
+                pending: Vec::new(),
+                undone: Vec::new()
+            }))
+        }
+    }
+
+    /// returns the subscriber that feeds the report, to add to the game's event bus
+    pub fn recorder(&self) -> Box<dyn game::Subscriber> {
+        Box::new(Recorder(self.journal.clone()))
+    }
+
+    /// works out the report from everything that has happened so far
+    pub fn report(&self) -> Report {
+        let journal = self.journal.borrow();
+        let mut units = journal.roster.clone();
+        let mut timeline: Vec<Turn> = Vec::new();
+        let mut outcome = None;
+        let mut round = 0;
+        // the unit and item whose blast is going off, so each target it
+        // catches isn't counted as another use
+        let mut blast: Option<(u64, String)> = None;
+
+        let events = journal.steps.iter().flatten().chain(journal.pending.iter());
+        for event in events {
+            let mut entry = None;
+            match event {
+                game::Event::TurnStarted { name, team, round: r, .. } => {
+                    round = *r;
+                    blast = None;
+                    timeline.push(Turn {
+                        round,
+                        actor: Some((name.clone(), *team)),
+                        entries: Vec::new()
+                    });
+                },
+                game::Event::UnitMoved { unit_id, to, .. } => {
+                    if let Some(u) = units.iter_mut().find(|u| u.id == *unit_id) {
+                        u.tiles_moved += 1;
+                    }
+                    // a run of steps is one move on the timeline
+                    let last = timeline.last_mut().and_then(|t| t.entries.last_mut());
+                    match last {
+                        Some(last) if last.starts_with("moved ") => {
+                            let tiles = last[6..].split(' ').next().and_then(|n| n.parse::<u64>().ok()).unwrap_or(0);
+                            *last = format!("moved {} tile(s) to {}", tiles + 1, to);
+                        },
+                        _ => entry = Some(format!("moved 1 tile(s) to {}", to))
+                    }
+                },
+                game::Event::AttackResolved { attacker_id, attacker, target_id, target, attack, damage, result, reaction } => {
+                    let part_of_blast = blast.as_ref() == Some(&(*attacker_id, attack.clone()));
+                    if let Some(u) = units.iter_mut().find(|u| u.id == *attacker_id) {
+                        u.damage_dealt += damage;
+                        if !part_of_blast {
+                            u.used(attack);
+                        }
+                        if let game::AttackResult::Killed = result {
+                            u.kills += 1;
+                        }
+                    }
+                    if let Some(u) = units.iter_mut().find(|u| u.id == *target_id) {
+                        u.damage_taken += damage;
+                    }
+
+                    let result = match result {
+                        game::AttackResult::Hit { .. } => format!("hit for {}", damage),
+                        game::AttackResult::Killed => format!("killed with {}", damage),
+                        game::AttackResult::Missed => "missed".to_string()
+                    };
+                    entry = Some(if *reaction {
+                        format!("{} fired {} from overwatch at {}, {}", attacker, attack, target, result)
+                    } else {
+                        format!("{} at {}, {}", attack, target, result)
+                    });
+                },
+                game::Event::UnitDied { unit_id, .. } => {
+                    if let Some(u) = units.iter_mut().find(|u| u.id == *unit_id) {
+                        u.killed_round = Some(round);
+                    }
+                },
+                game::Event::ItemUsed { unit_id, item, tile, .. } => {
+                    if let Some(u) = units.iter_mut().find(|u| u.id == *unit_id) {
+                        u.used(&item.name());
+                    }
+                    if let game::Effect::Blast { .. } = item.effect() {
+                        blast = Some((*unit_id, item.name()));
+                    }
+                    entry = Some(format!("used {} on {}", item.name(), tile));
+                },
+                game::Event::UnitHealed { name, health, max_health, .. } => {
+                    entry = Some(format!("{} healed to {}/{}", name, health, max_health));
+                },
+                game::Event::UnitRevived { unit_id, name, health } => {
+                    if let Some(u) = units.iter_mut().find(|u| u.id == *unit_id) {
+                        u.killed_round = None;
+                    }
+                    entry = Some(format!("{} revived with {} hp", name, health));
+                },
+                game::Event::BuffApplied { name, kind, amount, .. } => {
+                    entry = Some(format!("{} got +{} {}", name, amount, kind));
+                },
+                game::Event::LevelUp { name, level, .. } => {
+                    entry = Some(format!("{} reached level {}", name, level));
+                },
+                game::Event::UnitSpawned { unit_id, name, team, position } => {
+                    units.push(UnitStats::new(*unit_id, name.clone(), *team, round));
+                    entry = Some(format!("{} ({}) arrived at {}", name, team, position));
+                },
+                // whatever happens before the next turn starts gets an entry of its own
+                game::Event::RoundEnded { .. } => timeline.push(Turn {
+                    round,
+                    actor: None,
+                    entries: Vec::new()
+                }),
+                game::Event::GameOver { outcome: o } => outcome = Some(o.clone()),
+                _ => ()
+            }
+
+            if let Some(entry) = entry {
+                match timeline.last_mut() {
+                    Some(turn) => turn.entries.push(entry),
+                    None => timeline.push(Turn {
+                        round,
+                        actor: None,
+                        entries: vec![entry]
+                    })
+                }
+            }
+        }
+        timeline.retain(|t| t.actor.is_some() || !t.entries.is_empty());
+
+        // units that start the match come before the ones that join it
+        let mut teams: Vec<TeamStats> = Vec::new();
+        for unit in units.iter() {
+            let idx = match teams.iter().position(|t| t.team == unit.team) {
+                Some(a) => a,
+                None => {
+                    teams.push(TeamStats {
+                        team: unit.team,
+                        units: 0,
+                        survivors: 0,
+                        damage_dealt: 0,
+                        damage_taken: 0,
+                        kills: 0,
+                        tiles_moved: 0
+                    });
+                    teams.len() - 1
+                }
+            };
+            let team = &mut teams[idx];
+            team.units += 1;
+            if unit.killed_round.is_none() {
+                team.survivors += 1;
+            }
+            team.damage_dealt += unit.damage_dealt;
+            team.damage_taken += unit.damage_taken;
+            team.kills += unit.kills;
+            team.tiles_moved += unit.tiles_moved;
+        }
+
+        Report {
+            outcome,
+            rounds: round,
+            teams,
+            units,
+            timeline
+        }
+    }
+}
+
+/// quotes a string for json
+fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c)
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// quotes a csv field if it needs it
+fn csv_field(s: &str) -> String {
+    if s.contains(',') || s.contains('"') || s.contains('\n') {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// lists the attacks a unit used, e.g. `Shoot x3, Grenade x1`
+fn attack_list(attacks: &[(String, u64)], sep: &str) -> String {
+    let attacks: Vec<String> = attacks.iter().map(|(name, n)| format!("{} x{}", name, n)).collect();
+    attacks.join(sep)
+}
+
+impl Report {
+    /// returns the report as json
+    pub fn to_json(&self) -> String {
+        let mut out = String::from("{\n");
+        let (winner, reason) = match &self.outcome {
+            Some(game::Outcome::Winner(team, reason)) => (json_string(&team.to_string()), json_string(reason)),
+            Some(game::Outcome::Draw(reason)) => ("null".to_string(), json_string(reason)),
+            None => ("null".to_string(), json_string("unfinished"))
+        };
+        out.push_str(&format!("  \"winner\": {},\n  \"reason\": {},\n", winner, reason));
+        out.push_str(&format!("  \"rounds\": {},\n", self.rounds));
+
+        let teams: Vec<String> = self.teams.iter().map(|t| format!(
+            "    {{\"team\": {}, \"units\": {}, \"survivors\": {}, \"damage_dealt\": {}, \"damage_taken\": {}, \"kills\": {}, \"tiles_moved\": {}}}",
+            json_string(&t.team.to_string()),
+            t.units,
+            t.survivors,
+            t.damage_dealt,
+            t.damage_taken,
+            t.kills,
+            t.tiles_moved
+        )).collect();
+        out.push_str(&format!("  \"teams\": [\n{}\n  ],\n", teams.join(",\n")));
+
+        let units: Vec<String> = self.units.iter().map(|u| {
+            let attacks: Vec<String> = u.attacks.iter()
+                .map(|(name, n)| format!("{}: {}", json_string(name), n))
+                .collect();
+            let killed = match u.killed_round {
+                Some(r) => r.to_string(),
+                None => "null".to_string()
+            };
+            format!(
+                "    {{\"id\": {}, \"name\": {}, \"team\": {}, \"damage_dealt\": {}, \"damage_taken\": {}, \"kills\": {}, \"tiles_moved\": {}, \"attacks\": {{{}}}, \"joined_round\": {}, \"killed_round\": {}}}",
+                u.id,
+                json_string(&u.name),
+                json_string(&u.team.to_string()),
+                u.damage_dealt,
+                u.damage_taken,
+                u.kills,
+                u.tiles_moved,
+                attacks.join(", "),
+                u.joined_round,
+                killed
+            )
+        }).collect();
+        out.push_str(&format!("  \"units\": [\n{}\n  ],\n", units.join(",\n")));
+
+        let timeline: Vec<String> = self.timeline.iter().map(|t| {
+            let (unit, team) = match &t.actor {
+                Some((name, team)) => (json_string(name), json_string(&team.to_string())),
+                None => ("null".to_string(), "null".to_string())
+            };
+            let entries: Vec<String> = t.entries.iter().map(|e| json_string(e)).collect();
+            format!(
+                "    {{\"round\": {}, \"unit\": {}, \"team\": {}, \"events\": [{}]}}",
+                t.round,
+                unit,
+                team,
+                entries.join(", ")
+            )
+        }).collect();
+        out.push_str(&format!("  \"timeline\": [\n{}\n  ]\n}}\n", timeline.join(",\n")));
+        out
+    }
+
+    /// returns the per unit stats as csv, one row per unit
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("id,name,team,damage_dealt,damage_taken,kills,tiles_moved,attacks,joined_round,killed_round\n");
+        for u in self.units.iter() {
+            out.push_str(&format!(
+                "{},{},{},{},{},{},{},{},{},{}\n",
+                u.id,
+                csv_field(&u.name),
+                u.team,
+                u.damage_dealt,
+                u.damage_taken,
+                u.kills,
+                u.tiles_moved,
+                csv_field(&attack_list(&u.attacks, ";")),
+                u.joined_round,
+                u.killed_round.map(|r| r.to_string()).unwrap_or_default()
+            ));
+        }
+        out
+    }
+
+    /// writes the report to `path` as json or csv, going by its extension
+    pub fn export(&self, path: &str) -> Result<(), String> {
+        let out = match export_format(path)? {
+            ExportFormat::Json => self.to_json(),
+            ExportFormat::Csv => self.to_csv()
+        };
+        match std::fs::write(path, out) {
+            Ok(_) => Ok(()),
+            Err(e) => Err(format!("Failed to write after-action report '{}': {}", path, e))
+        }
+    }
+}
+
+/// the file formats the report can be exported as
+pub enum ExportFormat {
+    Json,
+    Csv
+}
+
+/// works out the export format from a file's extension
+pub fn export_format(path: &str) -> Result<ExportFormat, String> {
+    match std::path::Path::new(path).extension().and_then(|e| e.to_str()) {
+        Some(e) if e.eq_ignore_ascii_case("json") => Ok(ExportFormat::Json),
+        Some(e) if e.eq_ignore_ascii_case("csv") => Ok(ExportFormat::Csv),
+        _ => Err(format!("'{}' needs to end in .json or .csv", path))
+    }
+}
+
+/// display format implementation, the report as it's shown in the terminal
+impl std::fmt::Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(f, "==== After-action report ====")?;
+        match &self.outcome {
+            Some(outcome) => writeln!(f, "{} after {} round(s)", outcome, self.rounds)?,
+            None => writeln!(f, "Unfinished after {} round(s)", self.rounds)?
+        }
+
+        writeln!(f, "Teams:")?;
+        for t in self.teams.iter() {
+            writeln!(
+                f,
+                "\t{}: {}/{} units left, {} dmg dealt, {} dmg taken, {} kill(s), {} tile(s) moved",
+                t.team,
+                t.survivors,
+                t.units,
+                t.damage_dealt,
+                t.damage_taken,
+                t.kills,
+                t.tiles_moved
+            )?;
+        }
+
+        writeln!(f, "Units:")?;
+        for u in self.units.iter() {
+            let attacks = if u.attacks.is_empty() {
+                "no attacks".to_string()
+            } else {
+                attack_list(&u.attacks, ", ")
+            };
+            let joined = if u.joined_round > 0 {
+                format!(", joined in round {}", u.joined_round)
+            } else {
+                String::new()
+            };
+            writeln!(
+                f,
+                "\t[{}] {}: {} dmg dealt, {} dmg taken, {} kill(s), {} tile(s) moved, {}, {}{}",
+                u.team,
+                u.name,
+                u.damage_dealt,
+                u.damage_taken,
+                u.kills,
+                u.tiles_moved,
+                attacks,
+                u.fate(),
+                joined
+            )?;
+        }
+
+        writeln!(f, "Timeline:")?;
+        for t in self.timeline.iter() {
+            let who = match &t.actor {
+                Some((name, team)) => format!("[{}] {}", team, name),
+                None => "between turns".to_string()
+            };
+            if t.entries.is_empty() {
+                writeln!(f, "\tround {}, {}: nothing", t.round, who)?;
+            } else {
+                writeln!(f, "\tround {}, {}: {}", t.round, who, t.entries.join("; "))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// a match between one unit a side, bluefor's shooter being `name`
+    fn match_between(name: &str) -> (AfterAction, Box<dyn game::Subscriber>) {
+        let mut units = game::Units::new();
+        let stats = game::UnitStats::default();
+        units.insert(game::Unit::from_stats(name.to_string(), 0, game::Team::Bluefor, &stats, game::Position::new(0, 0)));
+        units.insert(game::Unit::from_stats("Red".to_string(), 1, game::Team::Redfor, &stats, game::Position::new(3, 0)));
+        let after_action = AfterAction::new(&units);
+        let recorder = after_action.recorder();
+        (after_action, recorder)
+    }
+
+    /// bluefor's shooter shooting the redfor unit
+    fn shot(attacker: &str, damage: u64, result: game::AttackResult) -> game::Event {
+        game::Event::AttackResolved {
+            attacker_id: 0,
+            attacker: attacker.to_string(),
+            target_id: 1,
+            target: "Red".to_string(),
+            attack: "Shoot".to_string(),
+            damage,
+            result,
+            reaction: false
+        }
+    }
+
+    /// plays a turn where bluefor steps forward and kills the redfor unit
+    fn play_turn(recorder: &mut Box<dyn game::Subscriber>, name: &str) {
+        recorder.notify(&game::Event::TurnStarted { unit_id: 0, name: name.to_string(), team: game::Team::Bluefor, round: 1 });
+        recorder.notify(&game::Event::UnitMoved {
+            unit_id: 0,
+            name: name.to_string(),
+            from: game::Position::new(0, 0),
+            to: game::Position::new(1, 0)
+        });
+        recorder.notify(&game::Event::Committed);
+        recorder.notify(&shot(name, 30, game::AttackResult::Hit { health: 70 }));
+        recorder.notify(&game::Event::Committed);
+        recorder.notify(&shot(name, 70, game::AttackResult::Killed));
+        recorder.notify(&game::Event::UnitDied {
+            unit_id: 1,
+            name: "Red".to_string(),
+            team: game::Team::Redfor,
+            position: game::Position::new(3, 0)
+        });
+        recorder.notify(&game::Event::Committed);
+    }
+
+    #[test]
+    fn csv_has_a_row_per_unit() {
+        let (after_action, mut recorder) = match_between("Blue");
+        play_turn(&mut recorder, "Blue");
+        assert_eq!(
+            after_action.report().to_csv(),
+            "id,name,team,damage_dealt,damage_taken,kills,tiles_moved,attacks,joined_round,killed_round\n\
+             0,Blue,Bluefor,100,0,1,1,Shoot x2,0,\n\
+             1,Red,Redfor,0,100,0,0,,0,1\n"
+        );
+    }
+
+    #[test]
+    fn undone_actions_drop_out_of_the_report() {
+        let (after_action, mut recorder) = match_between("Blue");
+        play_turn(&mut recorder, "Blue");
+        recorder.notify(&game::Event::Undone);
+        recorder.notify(&game::Event::Undone);
+        let csv = after_action.report().to_csv();
+        assert!(csv.contains("\n0,Blue,Bluefor,0,0,0,1,,0,\n"));
+        assert!(csv.contains("\n1,Red,Redfor,0,0,0,0,,0,\n"));
+
+        recorder.notify(&game::Event::Redone);
+        assert!(after_action.report().to_csv().contains("\n0,Blue,Bluefor,30,0,0,1,Shoot x1,0,\n"));
+    }
+
+    #[test]
+    fn json_has_the_outcome_teams_units_and_timeline() {
+        let (after_action, mut recorder) = match_between("Blue");
+        play_turn(&mut recorder, "Blue");
+        recorder.notify(&game::Event::GameOver {
+            outcome: game::Outcome::Winner(game::Team::Bluefor, "no more Redfor units".to_string())
+        });
+        let json = after_action.report().to_json();
+        assert!(json.starts_with("{\n  \"winner\": \"Bluefor\",\n  \"reason\": \"no more Redfor units\",\n  \"rounds\": 1,\n"));
+        assert!(json.contains("{\"team\": \"Bluefor\", \"units\": 1, \"survivors\": 1, \"damage_dealt\": 100, \"damage_taken\": 0, \"kills\": 1, \"tiles_moved\": 1}"));
+        assert!(json.contains("\"attacks\": {\"Shoot\": 2}, \"joined_round\": 0, \"killed_round\": null}"));
+        assert!(json.contains(
+            "{\"round\": 1, \"unit\": \"Blue\", \"team\": \"Bluefor\", \"events\": \
+             [\"moved 1 tile(s) to 1x0\", \"Shoot at Red, hit for 30\", \"Shoot at Red, killed with 70\"]}"
+        ));
+    }
+
+    #[test]
+    fn names_are_quoted_for_each_format() {
+        let name = "Billy \"the Kid\", Jr";
+        let (after_action, mut recorder) = match_between(name);
+        play_turn(&mut recorder, name);
+        let report = after_action.report();
+        assert!(report.to_csv().contains("\n0,\"Billy \"\"the Kid\"\", Jr\",Bluefor,"));
+        assert!(report.to_json().contains("\"name\": \"Billy \\\"the Kid\\\", Jr\""));
+        assert_eq!(json_string("a\\b\nc\u{1}"), "\"a\\\\b\\nc\\u0001\"");
+    }
+
+    #[test]
+    fn the_format_goes_by_the_extension() {
+        assert!(matches!(export_format("aar.JSON"), Ok(ExportFormat::Json)));
+        assert!(matches!(export_format("out/aar.csv"), Ok(ExportFormat::Csv)));
+        assert!(export_format("aar.txt").is_err());
+        assert!(export_format("aar").is_err());
+    }
+}
@@ -0,0 +1,260 @@
+// a simple computer player, and a way to play whole matches with it on both sides
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use crate::{
+    game,
+    Game
+};
+
+/// how a match played by the ai went
+pub struct Summary {
+    /// `None` if nobody had won by the round limit
+    pub outcome:    Option<game::Outcome>,
+    /// the team that won ties in the initiative order
+    pub first:      game::Team,
+    pub rounds:     u64,
+    pub turns:      u64
+}
+
+/// how good an attack is: a kill beats everything else, then the most
+/// damage, then the weakest target
+type AttackScore = (bool, u64, Reverse<u64>);
+
+/// picks the best attack the unit can make right now, along with its target
+fn pick_attack(g: &Game, unit: &game::Unit) -> Option<(u64, game::Attack)> {
+    let mut best: Option<(u64, game::Attack, AttackScore)> = None;
+    for attack in unit.attacks() {
+        if attack.damage() == 0 || attack.cost() > unit.action_points() {
+            continue;
+        }
+        for enemy in g.units.iter().filter(|u| u.team() != unit.team()) {
+            if g.board.distance(unit.position(), enemy.position()) > unit.attack_range(attack)
+                || !g.line_of_sight(unit.position(), enemy.position()) {
+                continue;
+            }
+            let score = (
+                attack.damage() >= enemy.health(),
+                attack.damage(),
+                Reverse(enemy.health())
+            );
+            if best.as_ref().is_none_or(|(_, _, s)| score > *s) {
+                best = Some((enemy.entity_id(), attack.clone(), score));
+            }
+        }
+    }
+    best.map(|(target, attack, _)| (target, attack))
+}
+
+/// returns the first step of the cheapest path from the unit to the nearest
+/// enemy it can reach, going around terrain and other units
+fn advance(g: &Game, unit: &game::Unit) -> Option<game::Movement> {
+    let enemies: Vec<u64> = g.units.iter()
+        .filter(|u| u.team() != unit.team())
+        .map(|u| u.entity_id())
+        .collect();
+
+    // dijkstra's search by what each step costs, carrying along the first
+    // step taken to reach each tile. ties go to whichever was found first,
+    // so when every step costs the same it's a plain breadth first search
+    let start = unit.position();
+    let cols = g.board.cols();
+    let mut best = vec![u64::MAX; g.board.rows() * cols];
+    best[start.x() * cols + start.y()] = 0;
+    // the queue holds the cost and when each tile was found, `found` holds
+    // the tile and first step
+    let mut queue = BinaryHeap::new();
+    let mut found = Vec::new();
+    for (mov, next) in g.board.neighbours(start) {
+        let cost = g.board.step_half_moves(mov);
+        best[next.x() * cols + next.y()] = cost;
+        queue.push((Reverse(cost), Reverse(found.len())));
+        found.push((next, mov));
+    }
+    while let Some((Reverse(cost), Reverse(idx))) = queue.pop() {
+        let (pos, mov) = found[idx];
+        if cost > best[pos.x() * cols + pos.y()] {
+            continue;
+        }
+        let tile = match g.board.tile(pos) {
+            Some(a) => a,
+            None => continue
+        };
+        if tile.occupant().is_some_and(|id| enemies.contains(&id)) {
+            return Some(mov);
+        }
+        if !tile.is_open() {
+            continue;
+        }
+        for (step, next) in g.board.neighbours(pos) {
+            let next_cost = cost + g.board.step_half_moves(step);
+            if next_cost < best[next.x() * cols + next.y()] {
+                best[next.x() * cols + next.y()] = next_cost;
+                queue.push((Reverse(next_cost), Reverse(found.len())));
+                found.push((next, mov));
+            }
+        }
+    }
+    None
+}
+
+/// how good a tile is to deploy on: more cover and fewer friends and
+/// enemies about first, then nearer the middle of the map
+type DeployScore = (i64, Reverse<usize>);
+
+/// picks the tile in its team's zone a unit is best off starting the battle
+/// on: behind cover facing the enemy, not next to friends one blast could
+/// catch too, and out of sight of any enemy it knows about
+fn pick_deployment(g: &Game, unit: &game::Unit) -> Option<game::Position> {
+    let deployment = g.deployment.as_ref()?;
+    let team = unit.team();
+    let placed = |u: &&game::Unit| !deployment.is_waiting(u.entity_id()) && !deployment.hides(u.team());
+    let allies: Vec<game::Position> = g.units.iter()
+        .filter(|u| u.team() == team)
+        .filter(placed)
+        .map(|u| u.position())
+        .collect();
+    let enemies: Vec<game::Position> = g.units.iter()
+        .filter(|u| u.team() != team)
+        .filter(placed)
+        .map(|u| u.position())
+        .collect();
+    // the enemy comes from the top of the map for redfor and the bottom for bluefor
+    let facing = |from: game::Position, to: game::Position| match team {
+        game::Team::Redfor => to.x() > from.x(),
+        game::Team::Bluefor => to.x() < from.x()
+    };
+    let topology = g.board.topology();
+
+    let mut best: Option<(game::Position, DeployScore)> = None;
+    for pos in deployment.zone(&g.board, team) {
+        if !g.board.is_open(pos) {
+            continue;
+        }
+        let mut score = 0;
+        for mov in topology.directions(game::Diagonals::Off) {
+            let next = match topology.offset(*mov).and_then(|(dx, dy)| pos.offset(dx, dy)) {
+                Some(a) => a,
+                None => continue
+            };
+            if g.board.tile(next).is_some_and(|t| t.terrain() == game::Terrain::Wall) {
+                score += if facing(pos, next) { 3 } else { 1 };
+            }
+        }
+        score -= 4 * allies.iter().filter(|a| g.board.distance(pos, **a) <= 2f64).count() as i64;
+        score -= 5 * enemies.iter().filter(|e| g.line_of_sight(**e, pos)).count() as i64;
+
+        let score = (score, Reverse(pos.y().abs_diff(g.board.cols() / 2)));
+        if best.as_ref().is_none_or(|(_, s)| score > *s) {
+            best = Some((pos, score));
+        }
+    }
+    best.map(|(pos, _)| pos)
+}
+
+/// has the ai place the units still waiting to deploy, only those of `team`
+/// if one is given and otherwise everyone in turn
+pub fn deploy(g: &mut Game, team: Option<game::Team>) {
+    loop {
+        let id = match (&g.deployment, team) {
+            (Some(d), Some(team)) => d.waiting_for(team).first().copied(),
+            (Some(d), None) => d.next(),
+            (None, _) => None
+        };
+        let unit = match id.map(|id| g.get_unit(id)) {
+            Some(Ok(a)) => a.clone(),
+            _ => return
+        };
+        let placed = pick_deployment(g, &unit)
+            .is_some_and(|pos| g.deploy_unit(unit.entity_id(), pos).is_ok());
+        // only a zone without room could stop it, which deployment checks for
+        if !placed {
+            return;
+        }
+    }
+}
+
+/// has the ai deploy both teams' units the way `mode` says
+pub fn deploy_all(g: &mut Game, mode: game::DeployMode) {
+    let units: Vec<u64> = g.units.iter().map(|u| u.entity_id()).collect();
+    if g.begin_deployment(mode, &units) {
+        deploy(g, None);
+    }
+}
+
+/// has the ai play out a unit's activation: attack when it can, otherwise
+/// close in on the nearest enemy, and bank whatever is left
+pub fn take_turn(g: &mut Game, unit_id: u64) {
+    loop {
+        let unit = match g.get_unit(unit_id) {
+            Ok(a) => a.clone(),
+            // killed by a reaction shot
+            Err(_) => return
+        };
+        if unit.action_points() == 0 {
+            return;
+        }
+
+        if let Some((target, attack)) = pick_attack(g, &unit) {
+            if g.do_attack(unit_id, target, attack).is_ok() {
+                continue;
+            }
+        }
+
+        if unit.action_points() >= game::MOVE_COST {
+            if let Some(mov) = advance(g, &unit) {
+                if g.move_unit(unit_id, mov).is_ok() {
+                    continue;
+                }
+            }
+        }
+
+        // nothing useful left to do
+        g.bank_points(unit_id);
+        return;
+    }
+}
+
+/// plays a whole match with the ai on both sides and nothing printed, giving
+/// up after `max_rounds`. nobody undoes an ai match, so no history is kept
+pub fn play_match(g: &mut Game, max_rounds: u64) -> Summary {
+    g.history.stop_recording();
+    let first = g.seize_initiative();
+    let summary = |g: &Game, outcome| Summary {
+        outcome,
+        first,
+        rounds: g.round,
+        turns: g.turns
+    };
+
+    loop {
+        if g.round_over() {
+            if g.round > 0 {
+                g.end_round();
+                if let Some(outcome) = g.check_victory(true) {
+                    return summary(g, Some(outcome));
+                }
+                if g.round >= max_rounds {
+                    return summary(g, None);
+                }
+            }
+            g.new_round();
+        }
+
+        if let Some(outcome) = g.check_victory(false) {
+            return summary(g, Some(outcome));
+        }
+
+        let unit = match g.find_next_unit() {
+            Ok(a) => a.clone(),
+            Err(_) => return summary(g, None)
+        };
+        if unit.action_points() == unit.max_action_points() {
+            g.start_activation(unit.entity_id());
+        }
+        take_turn(g, unit.entity_id());
+        g.end_turn();
+        g.commit_action();
+    }
+}
@@ -0,0 +1,923 @@
+// the commands players give their units, each working on the game through
+// the unit whose turn it is
+
+use crate::game;
+use crate::ai;
+use super::Game;
+
+/// converts a 1-based menu selection into an index, if it is in range
+fn menu_index(ustr: &str, len: usize) -> Option<usize> {
+    match ustr.trim().parse::<usize>() {
+        Ok(a) if a >= 1 && a <= len => Some(a-1),
+        _ => None
+    }
+}
+
+/// finds an entry of a menu by its number or (partial) name
+fn find_named(names: &[String], arg: &str) -> Option<usize> {
+    if let Some(idx) = menu_index(arg, names.len()) {
+        return Some(idx);
+    }
+
+    let arg = arg.to_lowercase();
+    names.iter().position(|n| n.to_lowercase().starts_with(&arg))
+}
+
+/// finds an attack by its menu number or (partial) name
+fn find_attack(unit: &game::Unit, arg: &str) -> Option<usize> {
+    let names: Vec<String> = unit.attacks().iter().map(|a| a.name()).collect();
+    find_named(&names, arg)
+}
+
+/// finds an item by its menu number or (partial) name
+fn find_item(unit: &game::Unit, arg: &str) -> Option<usize> {
+    let names: Vec<String> = unit.items().iter().map(|i| i.name()).collect();
+    find_named(&names, arg)
+}
+
+/// finds an ability by its menu number or (partial) name
+fn find_ability(unit: &game::Unit, arg: &str) -> Option<usize> {
+    let names: Vec<String> = unit.abilities().iter().map(|a| a.name()).collect();
+    find_named(&names, arg)
+}
+
+/// picks one of a unit's items from the arguments or by asking, `None` if the player backed out
+fn pick_item(game: &mut Game, unit: &game::Unit, arg: Option<&String>) -> Option<usize> {
+    if let Some(a) = arg {
+        let found = find_item(unit, a);
+        if found.is_none() {
+            println!("[-] {} has no item '{}'", unit.name(), a);
+        }
+        return found;
+    }
+
+    loop {
+        print_items(unit);
+        let ustr = game.input("Pick an item by number, or 'cancel' > ")?;
+        if ustr.trim().eq_ignore_ascii_case("cancel") {
+            return None;
+        }
+        match menu_index(&ustr, unit.items().len()) {
+            Some(a) => return Some(a),
+            None => println!("[-] That was not a valid number. Select the item by the number to the left of it")
+        }
+    }
+}
+
+/// prints the items a unit is carrying
+fn print_items(unit: &game::Unit) {
+    println!("{} is carrying:", unit.name());
+    for (idx, item) in unit.items().iter().enumerate() {
+        println!("\t{}. {}", idx + 1, item);
+    }
+}
+
+//////////////// ACTION CMDS //////////////////////////
+/// Attack a target
+pub(super) fn attack(game: &mut Game, unit_id: u64, args: &[String]) -> game::ErrorOut {
+    // get the attacker's unit information
+    let s = match game.get_unit(unit_id) {
+        Ok(a) => a.clone(),
+        Err(e) => {
+            println!("[-] {}", e);
+            return game::ErrorOut::FailedGeneric
+        }
+    };
+
+    loop {
+        // determine which attack we should use
+        let attack_idx = match args.first() {
+            Some(a) => match find_attack(&s, a) {
+                Some(idx) => idx,
+                None => {
+                    println!("[-] {} has no attack '{}'", s.name(), a);
+                    return game::ErrorOut::SuccessIncomplete;
+                }
+            },
+            None => {
+                println!("{} can use the following attacks:", s.name());
+                let mut idx = 0;
+                for attack in s.attacks() {
+                    idx += 1;
+                    println!(
+                        "\t{}. {} (range {}, dmg {}, {} AP)",
+                        idx,
+                        attack.name(),
+                        s.attack_range(attack),
+                        attack.damage(),
+                        attack.cost()
+                    );
+                }
+                println!("\t{}% chance to hit", s.accuracy());
+
+                let ustr = match game.input("Attack with which number > ") {
+                    Some(a) => a,
+                    None => return game::ErrorOut::Aborted
+                };
+
+                // get the index of the attack to use
+                match menu_index(&ustr, s.attacks().len()) {
+                    Some(a) => a,
+                    None => {
+                        println!("[-] That was not a valid number. Select the attack by the number to the left of it");
+                        continue
+                    }
+                }
+            }
+        };
+
+        // bail if we selected "Cancel"
+        if s.attacks()[attack_idx].name() == "Cancel" {
+            return game::ErrorOut::SuccessIncomplete;
+        }
+
+        // make sure we can afford the attack
+        if s.attacks()[attack_idx].cost() > s.action_points() {
+            println!("[-] {}", game::GameError::NotEnoughPoints {
+                cost: s.attacks()[attack_idx].cost(),
+                left: s.action_points()
+            });
+            if args.is_empty() {
+                continue;
+            }
+            return game::ErrorOut::SuccessIncomplete;
+        }
+
+        // now try to figure out what targets are within range and add them to a vector
+        let mut uctr = 0;
+        let mut tgt_vec: Vec<game::Unit> = Vec::new();
+        for u in game.units() {
+            // check if the attack is within range
+            if s.attack_range(&s.attacks()[attack_idx]) >= game.board.distance(s.position(), u.position())
+                && u.team() != s.team() {
+                uctr += 1;
+                if args.len() < 2 {
+                    if game.line_of_sight(s.position(), u.position()) {
+                        println!("\t{}: {}", uctr, u);
+                    } else {
+                        println!("\t{}: {} (no line of sight)", uctr, u);
+                    }
+                }
+                tgt_vec.push(u.clone());
+            }
+        }
+
+        if tgt_vec.is_empty() {
+            println!("[-] No targets in range!");
+            if args.is_empty() {
+                continue;
+            }
+            return game::ErrorOut::SuccessIncomplete;
+        }
+
+        let ustr = match args.get(1) {
+            Some(a) => a.clone(),
+            None => match game.input("Attack which unit > ") {
+                Some(a) => a,
+                None => return game::ErrorOut::Aborted
+            }
+        };
+
+        let target_idx = match menu_index(&ustr, tgt_vec.len()) {
+            Some(a) => a,
+            None => {
+                println!("[-] That was not a valid target. Select the target by the number to the left of it");
+                if args.len() < 2 {
+                    continue
+                }
+                return game::ErrorOut::SuccessIncomplete;
+            }
+        };
+        let target_id = tgt_vec[target_idx].entity_id();
+        // try to do the attack
+        match game.do_attack(
+            s.entity_id(),
+            target_id,
+            s.attacks()[attack_idx].clone()
+        ) {
+            // the result is reported through the game's events
+            Ok(_) => return game::ErrorOut::Success,
+            Err(e) => {
+                println!("[-] {}", e);
+                if !args.is_empty() {
+                    return game::ErrorOut::SuccessIncomplete;
+                }
+            }
+        }
+    }
+}
+
+/// Uses one of the unit's items on itself, an ally or a tile
+pub(super) fn use_item(game: &mut Game, unit_id: u64, args: &[String]) -> game::ErrorOut {
+    let s = game.get_unit(unit_id).unwrap().clone();
+    if s.items().is_empty() {
+        println!("[-] {} isn't carrying anything", s.name());
+        return game::ErrorOut::SuccessIncomplete;
+    }
+
+    loop {
+        let item_idx = match pick_item(game, &s, args.first()) {
+            Some(a) => a,
+            None => return game::ErrorOut::SuccessIncomplete
+        };
+        let item = s.items()[item_idx].clone();
+
+        // work out what the item is being used on
+        let tile = match item.target() {
+            game::ItemTarget::Ally => {
+                let allies: Vec<game::Unit> = game.units()
+                    .filter(|u| u.team() == s.team() && game.board.distance(s.position(), u.position()) <= item.range())
+                    .cloned()
+                    .collect();
+                if args.len() < 2 {
+                    for (idx, u) in allies.iter().enumerate() {
+                        println!("\t{}: {}", idx + 1, u);
+                    }
+                }
+
+                let ustr = match args.get(1) {
+                    Some(a) => a.clone(),
+                    None => match game.input(&format!("Use the {} on which unit > ", item.name())) {
+                        Some(a) => a,
+                        None => return game::ErrorOut::Aborted
+                    }
+                };
+                match menu_index(&ustr, allies.len()) {
+                    Some(a) => allies[a].position(),
+                    None => {
+                        println!("[-] That was not a valid unit. Select the unit by the number to the left of it");
+                        if args.len() < 2 {
+                            continue
+                        }
+                        return game::ErrorOut::SuccessIncomplete;
+                    }
+                }
+            },
+            game::ItemTarget::Tile => {
+                let ustr = match args.get(1) {
+                    Some(a) => a.clone(),
+                    None => match game.input(&format!("Use the {} on which tile, e.g. 3x4 > ", item.name())) {
+                        Some(a) => a,
+                        None => return game::ErrorOut::Aborted
+                    }
+                };
+                match ustr.trim().parse::<game::Position>() {
+                    Ok(a) => a,
+                    Err(e) => {
+                        println!("[-] {}", e);
+                        if args.len() < 2 {
+                            continue
+                        }
+                        return game::ErrorOut::SuccessIncomplete;
+                    }
+                }
+            }
+        };
+
+        match game.use_item(unit_id, item_idx, tile) {
+            Ok(_) => return game::ErrorOut::Success,
+            Err(e) => {
+                println!("[-] {}", e);
+                if !args.is_empty() {
+                    return game::ErrorOut::SuccessIncomplete;
+                }
+            }
+        }
+    }
+}
+
+/// Hands one of the unit's items to an adjacent ally
+pub(super) fn give(game: &mut Game, unit_id: u64, args: &[String]) -> game::ErrorOut {
+    let s = game.get_unit(unit_id).unwrap().clone();
+    let allies: Vec<game::Unit> = game.units()
+        .filter(|u| u.team() == s.team() && u.entity_id() != unit_id && game.board.distance(s.position(), u.position()) <= 1f64)
+        .cloned()
+        .collect();
+    if s.items().is_empty() {
+        println!("[-] {} isn't carrying anything", s.name());
+        return game::ErrorOut::SuccessIncomplete;
+    }
+    if allies.is_empty() {
+        println!("[-] Nobody is close enough to take it");
+        return game::ErrorOut::SuccessIncomplete;
+    }
+
+    let item_idx = match pick_item(game, &s, args.first()) {
+        Some(a) => a,
+        None => return game::ErrorOut::SuccessIncomplete
+    };
+
+    if args.len() < 2 {
+        for (idx, u) in allies.iter().enumerate() {
+            println!("\t{}: {}", idx + 1, u);
+        }
+    }
+    let ustr = match args.get(1) {
+        Some(a) => a.clone(),
+        None => match game.input("Give it to which unit > ") {
+            Some(a) => a,
+            None => return game::ErrorOut::Aborted
+        }
+    };
+    let ally = match menu_index(&ustr, allies.len()) {
+        Some(a) => &allies[a],
+        None => {
+            println!("[-] That was not a valid unit");
+            return game::ErrorOut::SuccessIncomplete;
+        }
+    };
+
+    match game.give_item(unit_id, item_idx, ally.entity_id()) {
+        Ok(_) => {
+            println!("[+] Handed the {} to {}", s.items()[item_idx].name(), ally.name());
+            game::ErrorOut::Success
+        },
+        Err(e) => {
+            println!("[-] {}", e);
+            game::ErrorOut::SuccessIncomplete
+        }
+    }
+}
+
+/// Takes a promotion from the skill tree, unlocking a new attack
+pub(super) fn promote(game: &mut Game, unit_id: u64, args: &[String]) -> game::ErrorOut {
+    let s = game.get_unit(unit_id).unwrap().clone();
+    let available = game.skills.available(&s);
+    if available.is_empty() {
+        println!(
+            "[-] {} has no promotions available ({} XP, level {})",
+            s.name(),
+            s.experience(),
+            s.level()
+        );
+        return game::ErrorOut::SuccessIncomplete;
+    }
+
+    let ustr = match args.first() {
+        Some(a) => a.clone(),
+        None => {
+            println!("{} can take one of these promotions:", s.name());
+            for (idx, p) in available.iter().enumerate() {
+                println!(
+                    "\t{}. {} (level {}): {} (range {}, dmg {}, {} AP)",
+                    idx + 1,
+                    p.name,
+                    p.level,
+                    p.attack.name(),
+                    p.attack.range(),
+                    p.attack.damage(),
+                    p.attack.cost()
+                );
+            }
+            match game.input("Promote with which number > ") {
+                Some(a) => a,
+                None => return game::ErrorOut::Aborted
+            }
+        }
+    };
+
+    // pick by number or by (partial) name
+    let lower = ustr.trim().to_lowercase();
+    let picked = match menu_index(&ustr, available.len()) {
+        Some(idx) => Some(idx),
+        None => available.iter().position(|p| p.name.to_lowercase().starts_with(&lower))
+    };
+    let promotion = match picked {
+        Some(idx) if !lower.is_empty() => &available[idx],
+        _ => {
+            println!("[-] That was not a valid promotion");
+            return game::ErrorOut::SuccessIncomplete;
+        }
+    };
+
+    game.promote_unit(unit_id, promotion);
+    println!("[+] {} is now a {} and learned {}!", s.name(), promotion.name, promotion.attack.name());
+    game::ErrorOut::Success
+}
+
+/// Uses one of the unit's support abilities on itself or a friendly unit
+pub(super) fn ability(game: &mut Game, unit_id: u64, args: &[String]) -> game::ErrorOut {
+    let s = game.get_unit(unit_id).unwrap().clone();
+    let abilities = s.abilities();
+
+    loop {
+        // determine which ability we should use
+        let ability_idx = match args.first() {
+            Some(a) => match find_ability(&s, a) {
+                Some(idx) => idx,
+                None => {
+                    println!("[-] {} has no ability '{}'", s.name(), a);
+                    return game::ErrorOut::SuccessIncomplete;
+                }
+            },
+            None => {
+                println!("{} can use the following abilities:", s.name());
+                for (idx, ab) in abilities.iter().enumerate() {
+                    println!("\t{}. {}", idx + 1, ab);
+                }
+                println!("\t{}. Cancel", abilities.len() + 1);
+
+                let ustr = match game.input("Use which number > ") {
+                    Some(a) => a,
+                    None => return game::ErrorOut::Aborted
+                };
+                match menu_index(&ustr, abilities.len() + 1) {
+                    Some(a) if a == abilities.len() => return game::ErrorOut::SuccessIncomplete,
+                    Some(a) => a,
+                    None => {
+                        println!("[-] That was not a valid number. Select the ability by the number to the left of it");
+                        continue
+                    }
+                }
+            }
+        };
+        let ab = &abilities[ability_idx];
+
+        // find the friendly units, or downed ones, it can reach
+        let in_reach = |u: &game::Unit| u.team() == s.team() && game.board.distance(s.position(), u.position()) <= ab.range();
+        let targets: Vec<game::Unit> = match ab.effect() {
+            game::AbilityEffect::Revive(_) => game.downed.iter().filter(|u| in_reach(u)).cloned().collect(),
+            _ => game.units().filter(|u| in_reach(u)).cloned().collect()
+        };
+        if targets.is_empty() {
+            println!("[-] Nobody in range to use {} on!", ab.name());
+            if args.is_empty() {
+                continue;
+            }
+            return game::ErrorOut::SuccessIncomplete;
+        }
+        if args.len() < 2 {
+            for (idx, u) in targets.iter().enumerate() {
+                println!("\t{}: {}", idx + 1, u);
+            }
+        }
+
+        let ustr = match args.get(1) {
+            Some(a) => a.clone(),
+            None => match game.input(&format!("Use {} on which unit > ", ab.name())) {
+                Some(a) => a,
+                None => return game::ErrorOut::Aborted
+            }
+        };
+        let target = match menu_index(&ustr, targets.len()) {
+            Some(a) => &targets[a],
+            None => {
+                println!("[-] That was not a valid unit. Select the unit by the number to the left of it");
+                if args.len() < 2 {
+                    continue
+                }
+                return game::ErrorOut::SuccessIncomplete;
+            }
+        };
+
+        match game.use_ability(unit_id, ability_idx, target.position()) {
+            Ok(_) => return game::ErrorOut::Success,
+            Err(e) => {
+                println!("[-] {}", e);
+                if !args.is_empty() {
+                    return game::ErrorOut::SuccessIncomplete;
+                }
+            }
+        }
+    }
+}
+
+/// Move unit to new position
+pub(super) fn move_unit(game: &mut Game, unit_id: u64, args: &[String]) -> game::ErrorOut {
+    // number of tiles to move, defaulting to one
+    let count = match args.get(1) {
+        Some(a) => a.parse::<usize>().unwrap_or(1),
+        None => 1
+    };
+
+    loop {
+        let mov = match args.first() {
+            Some(a) => match a.parse::<game::Movement>() {
+                Ok(m) => m,
+                Err(e) => {
+                    println!("[-] {}", e);
+                    return game::ErrorOut::SuccessIncomplete;
+                }
+            },
+            None => {
+                // print the movement options the map allows
+                let directions = game.board.directions();
+                println!("Movement options: ");
+                for (idx, mov) in directions.iter().enumerate() {
+                    println!("\t{}. {}", idx + 1, mov);
+                }
+
+                // get the user's selection and convert it to an enum
+                let ustr = match game.input("Pick a movement > ") {
+                    Some(a) => a,
+                    None => return game::ErrorOut::Aborted
+                };
+                match ustr.trim().parse::<usize>() {
+                    Ok(_) => match menu_index(&ustr, directions.len()) {
+                        Some(idx) => directions[idx],
+                        None => {
+                            println!("[-] That was not a valid movement selection.");
+                            continue
+                        }
+                    },
+                    // allow the direction to be typed out too
+                    Err(_) => match ustr.trim().parse::<game::Movement>() {
+                        Ok(m) => m,
+                        Err(_) => {
+                            println!("[-] That was not a valid number. Select the movement by the number to the left of it");
+                            continue
+                        }
+                    }
+                }
+            }
+        };
+
+        // try to move the unit, one tile at a time
+        let mut moved = 0;
+        for _ in 0..count {
+            match game.move_unit(unit_id, mov) {
+                // reaction fire stops the unit in its tracks
+                Ok(game::ErrorOut::Interrupted) => {
+                    moved += 1;
+                    break
+                },
+                Ok(_) => moved += 1,
+                Err(e) => {
+                    println!("[-] {}", e);
+                    break
+                }
+            }
+        }
+
+        if moved > 0 {
+            break;
+        }
+        if !args.is_empty() || game.get_unit(unit_id).unwrap().action_points() < game::MOVE_COST {
+            return game::ErrorOut::SuccessIncomplete;
+        }
+    }
+
+    game::ErrorOut::Success
+}
+
+/// Shows the available commands, or the usage of one of them
+pub(super) fn help(game: &mut Game, _unit_id: u64, args: &[String]) -> game::ErrorOut {
+    let commands = game.commands();
+
+    // show detailed help for a single command
+    if let Some(name) = args.first() {
+        match commands.iter().find(|c| c.matches(name)) {
+            Some(comm) => {
+                println!("Usage: {}", comm.usage());
+                println!("\t{}", comm.help);
+                if !comm.aliases.is_empty() {
+                    println!("\tAliases: {}", comm.aliases.join(", "));
+                }
+                for arg in &comm.args {
+                    println!(
+                        "\t{}: {}{}",
+                        arg.name,
+                        arg.kind,
+                        if arg.optional { " (optional)" } else { "" }
+                    );
+                }
+            },
+            None => {
+                println!("[-] Command not found '{}'", name);
+                return game::ErrorOut::NotFound;
+            }
+        }
+        return game::ErrorOut::SuccessIncomplete;
+    }
+
+    println!("Available Commands:");
+    for comm in commands.iter() {
+        println!("\t{:<28} {}", comm.usage(), comm.help);
+    }
+    game::ErrorOut::SuccessIncomplete
+}
+
+/// Prints the health of the unit
+pub(super) fn health(game: &mut Game, unit_id: u64, _args: &[String]) -> game::ErrorOut {
+    let s = game.get_unit(unit_id).unwrap().clone();
+    println!("{} is at {}/{} hitpoints", s.name(), s.health(), s.max_health());
+    for buff in s.buffs().iter().filter(|b| b.last_round >= game.round) {
+        println!("\t+{} {} until the end of round {}", buff.amount, buff.kind, buff.last_round);
+    }
+    game::ErrorOut::SuccessIncomplete
+}
+
+/// Prints the unit's experience and what it has gained from it
+pub(super) fn experience(game: &mut Game, unit_id: u64, _args: &[String]) -> game::ErrorOut {
+    let s = game.get_unit(unit_id).unwrap().clone();
+    println!(
+        "{} is level {} with {} XP ({}% to hit, +{} range)",
+        s.name(),
+        s.level(),
+        s.experience(),
+        s.accuracy(),
+        s.range_bonus()
+    );
+    if !s.promotions().is_empty() {
+        println!("\tPromotions: {}", s.promotions().join(", "));
+    }
+    game::ErrorOut::SuccessIncomplete
+}
+
+/// Prints the items the unit is carrying
+pub(super) fn inventory(game: &mut Game, unit_id: u64, _args: &[String]) -> game::ErrorOut {
+    let s = game.get_unit(unit_id).unwrap().clone();
+    if s.items().is_empty() {
+        println!("{} isn't carrying anything", s.name());
+    } else {
+        print_items(&s);
+    }
+    if s.loaded_damage() > 0 {
+        println!("\tNext attack: +{} dmg", s.loaded_damage());
+    }
+    game::ErrorOut::SuccessIncomplete
+}
+
+/// Prints the position of the unit
+pub(super) fn position(game: &mut Game, unit_id: u64, _args: &[String]) -> game::ErrorOut {
+    let s = game.get_unit(unit_id).unwrap().clone();
+    println!("{} is at position {}", s.name(), s.position());
+    game::ErrorOut::SuccessIncomplete
+}
+
+/// Prints the action points of the unit
+pub(super) fn points(game: &mut Game, unit_id: u64, _args: &[String]) -> game::ErrorOut {
+    let s = game.get_unit(unit_id).unwrap().clone();
+    println!(
+        "{} has {}/{} AP left and {} AP banked",
+        s.name(),
+        s.action_points(),
+        s.max_action_points(),
+        s.banked_points()
+    );
+    game::ErrorOut::SuccessIncomplete
+}
+
+/// Ends the unit's turn with a reaction shot ready for the first enemy that moves into view
+pub(super) fn overwatch(game: &mut Game, unit_id: u64, args: &[String]) -> game::ErrorOut {
+    let s = game.get_unit(unit_id).unwrap().clone();
+
+    // use the attack asked for, or the longest reaching one we can afford
+    let attack = match args.first() {
+        Some(a) => match find_attack(&s, a) {
+            Some(idx) if s.attacks()[idx].name() != "Cancel" => s.attacks()[idx].clone(),
+            _ => {
+                println!("[-] {} has no attack '{}'", s.name(), a);
+                return game::ErrorOut::SuccessIncomplete;
+            }
+        },
+        None => {
+            let affordable = s.attacks()
+                .iter()
+                .filter(|a| a.name() != "Cancel" && a.cost() <= s.action_points())
+                .max_by(|a, b| a.range().partial_cmp(&b.range()).unwrap_or(std::cmp::Ordering::Equal));
+            match affordable {
+                Some(a) => a.clone(),
+                None => {
+                    println!("[-] {} can't afford any attack to overwatch with", s.name());
+                    return game::ErrorOut::SuccessIncomplete;
+                }
+            }
+        }
+    };
+    if attack.cost() > s.action_points() {
+        println!(
+            "[-] {} needs {} AP but only {} AP is left!",
+            attack.name(),
+            attack.cost(),
+            s.action_points()
+        );
+        return game::ErrorOut::SuccessIncomplete;
+    }
+
+    // the shot is paid for now, and whatever is left over is banked
+    let banked = s.action_points() - attack.cost();
+    game.set_overwatch(unit_id, Some(attack.clone()));
+    game.set_unit_points(unit_id, 0, s.banked_points() + banked);
+    println!(
+        "[+] {} is on overwatch with {} (range {})",
+        s.name(),
+        attack.name(),
+        s.attack_range(&attack)
+    );
+    if banked > 0 {
+        println!("[+] Banked {} AP for defense", banked);
+    }
+    game::ErrorOut::Success
+}
+
+/// Ends the unit's turn, banking whatever action points are left
+pub(super) fn end(game: &mut Game, unit_id: u64, _args: &[String]) -> game::ErrorOut {
+    let banked = game.bank_points(unit_id);
+    if banked > 0 {
+        println!("[+] Banked {} AP for defense", banked);
+    }
+    println!("[-] Turn ended!");
+    game::ErrorOut::Success
+}
+
+/// Moves the unit later in the initiative order
+pub(super) fn delay(game: &mut Game, unit_id: u64, args: &[String]) -> game::ErrorOut {
+    let places = args.first().map(|a| a.parse::<usize>().unwrap_or(1)).unwrap_or(1);
+    match game.delay_unit(unit_id, Some(places)) {
+        Ok(_) => {
+            println!("[+] Delayed by {} place(s)", places);
+            game::ErrorOut::Yielded
+        },
+        Err(e) => {
+            println!("[-] {}", e);
+            game::ErrorOut::SuccessIncomplete
+        }
+    }
+}
+
+/// Moves the unit to the back of the initiative order
+pub(super) fn hold(game: &mut Game, unit_id: u64, _args: &[String]) -> game::ErrorOut {
+    match game.delay_unit(unit_id, None) {
+        Ok(_) => {
+            println!("[+] Holding until everyone else has acted");
+            game::ErrorOut::Yielded
+        },
+        Err(e) => {
+            println!("[-] {}", e);
+            game::ErrorOut::SuccessIncomplete
+        }
+    }
+}
+
+/// Scrolls the map, or centres it on a tile or back on the unit
+pub(super) fn view(game: &mut Game, _unit_id: u64, args: &[String]) -> game::ErrorOut {
+    let current = game.viewport();
+    game.view = match args.first() {
+        None => None,
+        Some(a) => match (a.parse::<game::Movement>(), a.parse::<game::Position>()) {
+            (Ok(mov), _) => {
+                let count = match args.get(1) {
+                    Some(n) => n.parse::<usize>().unwrap_or(1),
+                    None => current.page(mov)
+                };
+                Some(current.scrolled(&game.board, mov, count))
+            },
+            (_, Ok(pos)) if game.board.contains(pos) => Some(pos),
+            (_, Ok(_)) => {
+                println!("[-] {}", game::GameError::OutOfBounds);
+                return game::ErrorOut::SuccessIncomplete;
+            },
+            (Err(e), Err(_)) => {
+                println!("[-] {}", e);
+                return game::ErrorOut::SuccessIncomplete;
+            }
+        }
+    };
+    game.print_grid();
+    game::ErrorOut::SuccessIncomplete
+}
+
+/// Prints the initiative order for the round
+pub(super) fn order(game: &mut Game, _unit_id: u64, _args: &[String]) -> game::ErrorOut {
+    game.print_order();
+    game::ErrorOut::SuccessIncomplete
+}
+
+/// Prints the victory conditions and how close each team is
+pub(super) fn objectives(game: &mut Game, _unit_id: u64, _args: &[String]) -> game::ErrorOut {
+    println!("Victory conditions:");
+    for (idx, cond) in game.scenario.conditions().iter().enumerate() {
+        match cond {
+            game::Condition::Elimination => println!("\tWipe out the enemy"),
+            game::Condition::TurnLimit { rounds } => println!(
+                "\tHighest score after round {} wins (round {}, Redfor {} / Bluefor {})",
+                rounds,
+                game.round,
+                game.scenario.score(&game.units, game::Team::Redfor),
+                game.scenario.score(&game.units, game::Team::Bluefor)
+            ),
+            game::Condition::HoldTiles { tiles, rounds } => {
+                let tiles: Vec<String> = tiles.iter().map(|t| t.to_string()).collect();
+                let held = match game.scenario.held(idx) {
+                    Some((team, n)) => format!("held by {} for {} round(s)", team, n),
+                    None => "not held".to_string()
+                };
+                println!("\tHold {} for {} rounds ({})", tiles.join(", "), rounds, held);
+            },
+            game::Condition::KillVip { unit_id, team } => match game.get_unit(*unit_id) {
+                Ok(u) => println!("\tKill the {} VIP, {} at {}", team, u.name(), u.position()),
+                Err(_) => println!("\tKill the {} VIP (dead)", team)
+            },
+            game::Condition::Extraction { team, zone } => {
+                let zone: Vec<String> = zone.iter().map(|t| t.to_string()).collect();
+                println!("\t{} reaches {}", team, zone.join(", "));
+            },
+            game::Condition::ScoreLimit { points } => println!(
+                "\tFirst to {} points from control points (Redfor {} / Bluefor {})",
+                points,
+                game.scenario.score(&game.units, game::Team::Redfor),
+                game.scenario.score(&game.units, game::Team::Bluefor)
+            )
+        }
+    }
+    for point in game.scenario.control_points() {
+        println!("\tControl point {}", point);
+    }
+    game::ErrorOut::SuccessIncomplete
+}
+
+/// asks the other player to agree before history is rewritten in a hot-seat game
+fn consent(game: &mut Game, unit_id: u64, what: &str) -> Option<bool> {
+    if !game.undo_consent {
+        return Some(true);
+    }
+
+    let team = game.get_unit(unit_id).unwrap().team();
+    loop {
+        let ustr = game.input(&format!(
+            "[{}] {} wants to {} the last action. Agree? (y/n) > ",
+            team.other_team(),
+            team,
+            what
+        ))?;
+        match ustr.trim().to_lowercase().as_str() {
+            "y" | "yes" => return Some(true),
+            "n" | "no" => return Some(false),
+            _ => println!("[-] Answer with 'y' or 'n'")
+        }
+    }
+}
+
+/// Undoes the last action
+pub(super) fn undo(game: &mut Game, unit_id: u64, _args: &[String]) -> game::ErrorOut {
+    if !game.history.can_undo() {
+        println!("[-] Nothing to undo!");
+        return game::ErrorOut::SuccessIncomplete;
+    }
+
+    match consent(game, unit_id, "undo") {
+        Some(true) => (),
+        Some(false) => {
+            println!("[-] Undo refused!");
+            return game::ErrorOut::SuccessIncomplete;
+        },
+        None => return game::ErrorOut::Aborted
+    }
+
+    game.undo_action();
+    println!("[+] Last action undone!");
+    game::ErrorOut::Undone
+}
+
+/// Redoes the last undone action
+pub(super) fn redo(game: &mut Game, _unit_id: u64, _args: &[String]) -> game::ErrorOut {
+    if !game.history.can_redo() {
+        println!("[-] Nothing to redo!");
+        return game::ErrorOut::SuccessIncomplete;
+    }
+
+    game.redo_action();
+    println!("[+] Action redone!");
+    game::ErrorOut::Undone
+}
+
+/// Places the unit on a tile in its team's deployment zone
+pub(super) fn place(game: &mut Game, unit_id: u64, args: &[String]) -> game::ErrorOut {
+    let pos = match args.first().map(|a| a.parse::<game::Position>()) {
+        Some(Ok(a)) => a,
+        Some(Err(e)) => {
+            println!("[-] {}", e);
+            return game::ErrorOut::SuccessIncomplete;
+        },
+        None => {
+            println!("[-] Place the unit on which tile? e.g. place 1x4");
+            return game::ErrorOut::SuccessIncomplete;
+        }
+    };
+
+    match game.deploy_unit(unit_id, pos) {
+        Ok(_) => {
+            println!("[+] {} deploys at {}", game.get_unit(unit_id).unwrap().name(), pos);
+            game::ErrorOut::Success
+        },
+        Err(e) => {
+            println!("[-] {}", e);
+            game::ErrorOut::SuccessIncomplete
+        }
+    }
+}
+
+/// Has the AI place the unit and the rest of its team
+pub(super) fn auto_deploy(game: &mut Game, unit_id: u64, _args: &[String]) -> game::ErrorOut {
+    let team = game.get_unit(unit_id).unwrap().team();
+    ai::deploy(game, Some(team));
+    println!("[+] {} is deployed", team);
+    game::ErrorOut::Success
+}
+
+/// End the game in a draw
+pub(super) fn endgame(_game: &mut Game, _unit_id: u64, _args: &[String]) -> game::ErrorOut {
+    println!("[+] Game complete!");
+    game::ErrorOut::Aborted
+}
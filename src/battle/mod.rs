@@ -0,0 +1,1986 @@
+// a battle being played, with the board, the units and everything that
+// happens to them, and the loop that plays it out
+
+use rand::prelude::*;
+use rand::Rng;
+use std::rc::Rc;
+
+use crate::ai;
+use crate::command::{
+    self,
+    ArgKind,
+    Command,
+    CommandArg
+};
+use crate::config;
+use crate::game;
+use crate::input::InputSource;
+use crate::mission;
+
+mod commands;
+use commands::{
+    ability,
+    attack,
+    auto_deploy,
+    delay,
+    end,
+    endgame,
+    experience,
+    give,
+    health,
+    help,
+    hold,
+    inventory,
+    move_unit,
+    objectives,
+    order,
+    overwatch,
+    place,
+    points,
+    position,
+    promote,
+    redo,
+    undo,
+    use_item,
+    view
+};
+
+/// creates the default squad of `size` units for a team
+pub(crate) fn default_squad(team: game::Team, first_id: u64, size: u64, stats: &game::UnitStats) -> Vec<game::Unit> {
+    let mut units = Vec::new();
+    for i in 0..size {
+        let name = match team {
+            game::Team::Bluefor => format!("Billy #{}", i+1),
+            game::Team::Redfor => format!("Billy but Bad #{}", i+1)
+        };
+        units.push(
+            game::Unit::from_stats(
+                name, 
+                first_id + i, 
+                team,
+                stats,
+                // note we will update these later on when we generate the board
+                game::Position::new(0,0)
+            )
+        );
+    }
+    units
+}
+
+/// defines our game grid
+pub struct Game {
+    pub(crate) board:           game::Board,
+    pub(crate) units:           game::Units,
+    commands:                   Rc<[Command]>,
+    /// the commands while the teams are deploying
+    deploy_commands:            Rc<[Command]>,
+    input:                      InputSource,
+    rng:                        StdRng,
+    pub(crate) history:         game::History,
+    pub(crate) undo_consent:    bool,
+    pub(crate) round:           u64,
+    pub(crate) turns:           u64,
+    order:                      Vec<game::Roll>,
+    first:                      game::Team,
+    pub(crate) initiative_roll: bool,
+    pub(crate) scenario:        game::Scenario,
+    pub(crate) skills:          game::SkillTree,
+    downed:                     Vec<game::Unit>,
+    pub(crate) events:          game::EventBus,
+    pub(crate) mission:         Option<mission::Mission>,
+    /// the tile the player has scrolled the map to, `None` to follow the active unit
+    view:                       Option<game::Position>,
+    /// who is still to be placed, while the teams are deploying
+    pub(crate) deployment:      Option<game::Deployment>,
+    /// what units raised during the battle start with
+    pub(crate) stats:           game::UnitStats
+}
+
+impl Game {
+    /// creates a new game on a map laid out by `map` with `squad_size`
+    /// default units a side, generating it from `rng`
+    pub fn new(map: &game::MapSettings, squad_size: u64, rng: StdRng) -> Self {
+        // create our BLUEFOR and REDFOR units
+        let stats = game::UnitStats::default();
+        let mut units = default_squad(game::Team::Bluefor, 0, squad_size, &stats);
+        units.append(&mut default_squad(game::Team::Redfor, squad_size, squad_size, &stats));
+
+        Self::with_units(map, rng, units)
+    }
+
+    /// sets up a match from `settings` without anyone at the terminal: each
+    /// team's saved army or default squad, the rules, the skill tree and the
+    /// scenario. armies can't be built here since there's no one to buy them
+    pub fn from_settings(settings: &config::Settings, rng: StdRng) -> Result<Self, String> {
+        if settings.build_army {
+            return Err("Armies can only be built at the terminal, use --army to field saved ones".to_string());
+        }
+        // bluefor gets the first ids, then redfor
+        let mut units = settings.army(game::Team::Bluefor, 0)?;
+        let mut redfor = settings.army(game::Team::Redfor, units.len() as u64)?;
+        units.append(&mut redfor);
+
+        let mut g = Self::with_units(&settings.map, rng, units);
+        g.apply_settings(settings)?;
+        Ok(g)
+    }
+
+    /// plays by the rules in `settings`, and sets up their skill tree and
+    /// scenario
+    pub(crate) fn apply_settings(&mut self, settings: &config::Settings) -> Result<(), String> {
+        self.stats = settings.stats.clone();
+        self.undo_consent = settings.undo_consent;
+        self.initiative_roll = !settings.fixed_initiative;
+        self.skills = config::load_skills(&settings.options.skills)?;
+        setup_scenario(self, &settings.options)
+    }
+
+    /// creates a new game with the given units, generating the map from `rng`
+    pub(crate) fn with_units(map: &game::MapSettings, mut rng: StdRng, mut units: Vec<game::Unit>) -> Self {
+        let mut board = game::Board::new(map);
+        let (rows, cols) = (board.rows(), board.cols());
+        let mut commands: Vec<Command> = Vec::new();
+
+        // scatter cover over every row between the two deployment zones
+        for x in 1..rows.saturating_sub(1) {
+            for y in 0..cols {
+                // roll out of 100 for each tile, and add cover if it's under the map's percentage
+                if rng.gen_range(0..100) < map.cover {
+                    if let Some(tile) = board.tile_mut(game::Position::new(x, y)) {
+                        tile.set_terrain(game::Terrain::Wall);
+                    }
+                }
+            }
+        }
+
+        // REDFOR lines up in the first row from the left corner, and
+        // BLUEFOR in the last row up to the right corner. a team too big for
+        // one row carries on into the next one in, clearing any cover there
+        let blue = units.iter().filter(|u| u.team() == game::Team::Bluefor).count();
+        let mut rctr = 0;
+        let mut bctr = 0;
+        for soldier in units.iter_mut() {
+            let pos = match soldier.team() {
+                game::Team::Redfor => {
+                    rctr += 1;
+                    game::Position::new((rctr - 1) / cols, (rctr - 1) % cols)
+                },
+                game::Team::Bluefor => {
+                    bctr += 1;
+                    let row = (bctr - 1) / cols;
+                    // the last, partly filled row still ends at the right corner
+                    let in_row = (blue - row * cols).min(cols);
+                    game::Position::new(
+                        rows.saturating_sub(row + 1),
+                        cols - in_row + (bctr - 1) % cols
+                    )
+                }
+            };
+            soldier.move_unit(pos);
+            if let Some(tile) = board.tile_mut(pos) {
+                tile.set_terrain(game::Terrain::Open);
+                tile.set_occupant(Some(soldier.entity_id()));
+            }
+        }
+
+        // create all of our commands
+        // "attack"
+        commands.push(
+            Command::new("attack", "Attack an enemy", attack)
+                .with_aliases(&["a", "atk"])
+                .with_arg(CommandArg::optional("attack", ArgKind::Attack))
+                .with_arg(CommandArg::optional("target", ArgKind::Target))
+        );
+        // "ability"
+        commands.push(
+            Command::new("ability", "Use a support ability on yourself or an ally", ability)
+                .with_aliases(&["ab", "support"])
+                .with_arg(CommandArg::optional("ability", ArgKind::Ability))
+                .with_arg(CommandArg::optional("target", ArgKind::Target))
+        );
+        // "move"
+        commands.push(
+            Command::new("move", "Move to a new position", move_unit)
+                .with_aliases(&["m", "mv"])
+                .with_arg(CommandArg::optional("direction", ArgKind::Direction))
+                .with_arg(CommandArg::optional("count", ArgKind::Number))
+        );
+        // "use"
+        commands.push(
+            Command::new("use", "Use an item on yourself, an ally or a tile", use_item)
+                .with_aliases(&["item"])
+                .with_arg(CommandArg::optional("item", ArgKind::Item))
+                .with_arg(CommandArg::optional("target", ArgKind::TargetOrTile))
+        );
+        // "give"
+        commands.push(
+            Command::new("give", "Hand an item to an ally next to you", give)
+                .with_aliases(&["hand"])
+                .with_arg(CommandArg::optional("item", ArgKind::Item))
+                .with_arg(CommandArg::optional("ally", ArgKind::Target))
+        );
+        // "inventory"
+        commands.push(
+            Command::new("inventory", "Shows the items your unit is carrying", inventory)
+                .with_aliases(&["inv", "i"])
+        );
+        // "health"
+        commands.push(
+            Command::new("health", "Shows the health of your unit", health)
+                .with_aliases(&["hp"])
+        );
+        // "experience"
+        commands.push(
+            Command::new("experience", "Shows your unit's level and experience", experience)
+                .with_aliases(&["xp", "level"])
+        );
+        // "promote"
+        commands.push(
+            Command::new("promote", "Take a promotion, unlocking a new attack", promote)
+                .with_aliases(&["promo"])
+                .with_arg(CommandArg::optional("promotion", ArgKind::Promotion))
+        );
+        // "position"
+        commands.push(
+            Command::new("position", "Shows the position of your unit", position)
+                .with_aliases(&["pos"])
+        );
+        // "points"
+        commands.push(
+            Command::new("points", "Shows your unit's action points", points)
+                .with_aliases(&["ap"])
+        );
+        // "delay"
+        commands.push(
+            Command::new("delay", "Act later in the initiative order", delay)
+                .with_aliases(&["wait"])
+                .with_arg(CommandArg::optional("places", ArgKind::Number))
+        );
+        // "hold"
+        commands.push(
+            Command::new("hold", "Act after everyone else this round", hold)
+        );
+        // "view"
+        commands.push(
+            Command::new("view", "Scrolls the map, or centres it on a tile or back on your unit", view)
+                .with_aliases(&["v", "look"])
+                .with_arg(CommandArg::optional("direction or tile", ArgKind::DirectionOrTile))
+                .with_arg(CommandArg::optional("count", ArgKind::Number))
+        );
+        // "order"
+        commands.push(
+            Command::new("order", "Shows the initiative order for the round", order)
+                .with_aliases(&["queue"])
+        );
+        // "objectives"
+        commands.push(
+            Command::new("objectives", "Shows how to win and who is ahead", objectives)
+                .with_aliases(&["obj", "goals"])
+        );
+        // "overwatch"
+        commands.push(
+            Command::new("overwatch", "End your turn ready to shoot the first enemy that moves into view", overwatch)
+                .with_aliases(&["ow", "watch"])
+                .with_arg(CommandArg::optional("attack", ArgKind::Attack))
+        );
+        // "end"
+        commands.push(
+            Command::new("end", "End your unit's turn, banking any AP left", end)
+                .with_aliases(&["e", "done"])
+        );
+        // "undo"
+        commands.push(
+            Command::new("undo", "Takes back the last action", undo)
+                .with_aliases(&["u"])
+        );
+        // "redo"
+        commands.push(
+            Command::new("redo", "Replays the last undone action", redo)
+        );
+        // "endgame"
+        commands.push(
+            Command::new("endgame", "Ends the game in a draw", endgame)
+                .with_aliases(&["quit", "exit"])
+        );
+        // "help"
+        commands.push(
+            Command::new("help", "Shows all of the commands", help)
+                .with_aliases(&["h", "?"])
+                .with_arg(CommandArg::optional("command", ArgKind::Command))
+        );
+
+        // the deployment phase only has a few of them
+        let deploy_commands: Vec<Command> = vec![
+            Command::new("place", "Place your unit on a tile in your deployment zone", place)
+                .with_aliases(&["p", "deploy"])
+                .with_arg(CommandArg::optional("tile", ArgKind::Tile)),
+            Command::new("auto", "Let the AI place this unit and the rest of your team", auto_deploy)
+                .with_aliases(&["auto-deploy"]),
+            Command::new("view", "Scrolls the map, or centres it on a tile or back on your zone", view)
+                .with_aliases(&["v", "look"])
+                .with_arg(CommandArg::optional("direction or tile", ArgKind::DirectionOrTile))
+                .with_arg(CommandArg::optional("count", ArgKind::Number)),
+            Command::new("help", "Shows all of the commands", help)
+                .with_aliases(&["h", "?"])
+                .with_arg(CommandArg::optional("command", ArgKind::Command))
+        ];
+
+        Game {
+            board,
+            units: units.into_iter().collect(),
+            commands: commands.into(),
+            deploy_commands: deploy_commands.into(),
+            input: InputSource::stdin(),
+            rng,
+            history: game::History::new(),
+            undo_consent: false,
+            round: 0,
+            turns: 0,
+            order: Vec::new(),
+            first: game::Team::Redfor,
+            initiative_roll: true,
+            scenario: game::Scenario::new(),
+            skills: game::SkillTree::default_tree(),
+            downed: Vec::new(),
+            events: game::EventBus::new(),
+            mission: None,
+            view: None,
+            deployment: None,
+            stats: game::UnitStats::default()
+        }
+    }
+
+    /// replaces where the game reads player input from
+    pub(crate) fn set_input(&mut self, input: InputSource) {
+        self.input = input;
+    }
+
+    /// hands back where the game was reading player input from
+    pub(crate) fn take_input(&mut self) -> InputSource {
+        std::mem::replace(&mut self.input, InputSource::stdin())
+    }
+
+    /// prints the prompt and reads a line of player input, `None` once input runs out
+    fn input(&mut self, prompt: &str) -> Option<String> {
+        self.input.read(prompt)
+    }
+
+    /// returns the units currently in the game
+    pub(crate) fn units(&self) -> impl Iterator<Item = &game::Unit> + '_ {
+        self.units.iter()
+    }
+
+    /// returns all the available commands for the game, shared rather than
+    /// copied, or just the deployment ones while the teams are deploying
+    fn commands(&self) -> Rc<[Command]> {
+        match self.deployment {
+            Some(_) => self.deploy_commands.clone(),
+            None => self.commands.clone()
+        }
+    }
+    
+    /// sets a unit's remaining and banked action points, recording the change
+    fn set_unit_points(&mut self, id: u64, action_points: u64, banked_points: u64) {
+        if let Some(unit) = self.units.get_mut(id) {
+            let before = (unit.action_points(), unit.banked_points());
+            unit.set_points(action_points, banked_points);
+            self.history.record(game::Action::Points {
+                unit_id: id,
+                before,
+                after: (action_points, banked_points)
+            });
+        }
+    }
+
+    /// spends action points for a unit, returning false if it can't afford them
+    fn spend_points(&mut self, id: u64, cost: u64) -> bool {
+        let unit = match self.get_unit(id) {
+            Ok(a) => a.clone(),
+            Err(_) => return false
+        };
+        if unit.action_points() < cost {
+            return false;
+        }
+
+        self.set_unit_points(id, unit.action_points() - cost, unit.banked_points());
+        true
+    }
+
+    /// banks whatever action points a unit has left, returning how many
+    pub(crate) fn bank_points(&mut self, id: u64) -> u64 {
+        let unit = match self.get_unit(id) {
+            Ok(a) => a.clone(),
+            Err(_) => return 0
+        };
+
+        let banked = unit.action_points();
+        self.set_unit_points(id, 0, unit.banked_points() + banked);
+        banked
+    }
+
+    /// starts a unit's activation, dropping the points it banked and any
+    /// reaction shot it didn't get to take
+    pub(crate) fn start_activation(&mut self, id: u64) {
+        let unit = match self.get_unit(id) {
+            Ok(a) => a.clone(),
+            Err(_) => return
+        };
+
+        if unit.banked_points() > 0 {
+            self.set_unit_points(id, unit.action_points(), 0);
+        }
+        if unit.overwatch().is_some() {
+            self.set_overwatch(id, None);
+        }
+        if unit.diagonal_steps() > 0 {
+            self.change_unit(id, |u| u.set_diagonal_steps(0));
+        }
+        self.events.emit(game::Event::TurnStarted {
+            unit_id: id,
+            name: unit.name(),
+            team: unit.team(),
+            round: self.round
+        });
+    }
+
+    /// arms or stands down a unit's reaction shot, recording the change
+    fn set_overwatch(&mut self, id: u64, attack: Option<game::Attack>) {
+        self.change_unit(id, |u| u.set_overwatch(attack));
+    }
+
+    /// has every enemy on overwatch that a unit just came into reach of by
+    /// stepping off `from` take a shot at it, returning if anyone fired
+    fn reaction_fire(&mut self, mover: u64, from: game::Position) -> bool {
+        let team = match self.get_unit(mover) {
+            Ok(a) => a.team(),
+            Err(_) => return false
+        };
+        let watchers: Vec<game::Unit> = self.units.iter()
+            .filter(|u| u.team() != team && u.overwatch().is_some())
+            .cloned()
+            .collect();
+
+        let mut fired = false;
+        for watcher in watchers {
+            // stop once the target is down
+            let target = match self.get_unit(mover) {
+                Ok(a) => a.clone(),
+                Err(_) => break
+            };
+            // only the step that brings the target into reach draws fire
+            let attack = watcher.overwatch().unwrap();
+            let in_reach = |pos: game::Position| {
+                self.board.distance(watcher.position(), pos) <= watcher.attack_range(&attack)
+                    && self.line_of_sight(watcher.position(), pos)
+            };
+            if in_reach(from) || !in_reach(target.position()) {
+                continue;
+            }
+
+            // the shot was paid for when overwatch was armed, so hand the
+            // points back just long enough to take it. overwatch stands down
+            // after the shot so it is still reported as a reaction
+            self.set_unit_points(watcher.entity_id(), attack.cost(), watcher.banked_points());
+            if self.do_attack(watcher.entity_id(), target.entity_id(), attack).is_err() {
+                self.set_unit_points(watcher.entity_id(), 0, watcher.banked_points());
+            }
+            self.set_overwatch(watcher.entity_id(), None);
+            fired = true;
+        }
+        fired
+    }
+
+    /// returns if nothing but open ground lies between two tiles
+    pub(crate) fn line_of_sight(&self, from: game::Position, to: game::Position) -> bool {
+        self.board.line_of_sight(from, to)
+    }
+
+    /// ends the active unit's turn
+    pub(crate) fn end_turn(&mut self) {
+        self.turns += 1;
+        self.history.record(game::Action::TurnEnded);
+    }
+
+    /// scores the round that just finished towards the victory conditions
+    pub(crate) fn end_round(&mut self) {
+        let before = self.scenario.clone();
+        self.scenario.end_round(&self.board, &self.units);
+        self.history.record(game::Action::Objectives {
+            before,
+            after: self.scenario.clone()
+        });
+        self.events.emit(game::Event::RoundEnded { round: self.round });
+    }
+
+    /// ends the match with `outcome` the next time the victory conditions are checked
+    pub(crate) fn declare_outcome(&mut self, outcome: game::Outcome) {
+        let before = self.scenario.clone();
+        self.scenario.declare(outcome);
+        self.history.record(game::Action::Objectives {
+            before,
+            after: self.scenario.clone()
+        });
+    }
+
+    /// lets the mission script react to everything that happened since it last ran
+    fn run_mission(&mut self) {
+        if let Some(mut mission) = self.mission.take() {
+            mission.run(self);
+            self.mission = Some(mission);
+        }
+    }
+
+    /// checks the victory conditions, returning the outcome if the match is over
+    pub(crate) fn check_victory(&self, round_over: bool) -> Option<game::Outcome> {
+        self.scenario.evaluate(&self.units, self.round, round_over)
+    }
+
+    /// clears terrain off of objective tiles, failing if any are off the map
+    pub(crate) fn clear_tiles(&mut self, tiles: &[game::Position]) -> Result<(), String> {
+        for tile in tiles {
+            match self.board.tile_mut(*tile) {
+                Some(t) => t.set_terrain(game::Terrain::Open),
+                None => return Err(format!("{} is off the map", tile))
+            }
+        }
+        Ok(())
+    }
+
+    /// returns the glyph of the objective on an empty tile, if there is one
+    fn objective_glyph(&self, pos: game::Position) -> Option<char> {
+        if let Some(point) = self.scenario.control_point(pos) {
+            return Some(point.glyph());
+        }
+        for cond in self.scenario.conditions() {
+            match cond {
+                game::Condition::HoldTiles { tiles, .. } if tiles.contains(&pos) => return Some('*'),
+                game::Condition::Extraction { zone, .. } if zone.contains(&pos) => return Some('X'),
+                _ => ()
+            }
+        }
+        None
+    }
+
+    /// flips a coin for the team that wins ties in the initiative order, returning it
+    pub(crate) fn seize_initiative(&mut self) -> game::Team {
+        self.first = if self.rng.gen() {
+            game::Team::Bluefor
+        } else {
+            game::Team::Redfor
+        };
+        self.first
+    }
+
+    /// changes the damage of an attack for every unit that has it
+    pub fn set_attack_damage(&mut self, attack: &str, damage: u64) {
+        for unit in self.units.iter_mut() {
+            unit.set_attack_damage(attack, damage);
+        }
+    }
+
+    /// returns if every unit has used up its action points
+    pub(crate) fn round_over(&self) -> bool {
+        self.units.iter().all(|u| u.action_points() == 0)
+    }
+
+    /// begins a new round, refreshing everyone's action points
+    pub(crate) fn new_round(&mut self) {
+        let mut before = Vec::new();
+        for unit in self.units.iter_mut() {
+            before.push((unit.entity_id(), unit.action_points()));
+            unit.set_points(unit.max_action_points(), unit.banked_points());
+        }
+        self.round += 1;
+        self.history.record(game::Action::NewRound { before });
+
+        // roll for the order everyone acts in
+        let mut rolls = Vec::new();
+        for unit in self.units.iter() {
+            let roll = if self.initiative_roll {
+                self.rng.gen_range(1..=game::INITIATIVE_DIE)
+            } else {
+                0
+            };
+            rolls.push(game::Roll {
+                unit_id: unit.entity_id(),
+                team: unit.team(),
+                initiative: unit.initiative(),
+                roll
+            });
+        }
+        self.set_order(game::initiative_order(rolls, self.first));
+
+        // the round start goes with the turn that ended the last round, so
+        // undoing never has to roll initiative again
+        self.history.commit_round();
+        self.events.emit(game::Event::Amended);
+    }
+
+    /// replaces the initiative order, recording the change
+    fn set_order(&mut self, order: Vec<game::Roll>) {
+        let before = std::mem::replace(&mut self.order, order.clone());
+        self.history.record(game::Action::Reorder {
+            before,
+            after: order
+        });
+    }
+
+    /// moves a unit later in the initiative order, or to the back if `places` is `None`
+    fn delay_unit(&mut self, id: u64, places: Option<usize>) -> Result<(), game::GameError> {
+        self.check_turn(id)?;
+        let unit = self.get_unit(id)?;
+        if unit.action_points() != unit.max_action_points() {
+            return Err(game::GameError::AlreadyActed);
+        }
+
+        let idx = match self.order.iter().position(|r| r.unit_id == id) {
+            Some(a) => a,
+            None => return Err(game::GameError::UnknownUnit(id))
+        };
+
+        // only units that still have to act count as places to move behind
+        let waiting: Vec<usize> = self.order.iter()
+            .enumerate()
+            .skip(idx + 1)
+            .filter(|(_, r)| match self.get_unit(r.unit_id) {
+                Ok(u) => u.action_points() > 0,
+                Err(_) => false
+            })
+            .map(|(i, _)| i)
+            .collect();
+        if waiting.is_empty() {
+            return Err(game::GameError::NobodyLeftToAct);
+        }
+
+        let target = match places {
+            Some(p) => waiting[p.clamp(1, waiting.len()) - 1],
+            None => waiting[waiting.len() - 1]
+        };
+
+        let mut order = self.order.clone();
+        let roll = order.remove(idx);
+        order.insert(target, roll);
+        self.set_order(order);
+        Ok(())
+    }
+
+    /// prints the initiative order for the round
+    fn print_order(&self) {
+        println!("Initiative order for round {}:", self.round);
+        let mut idx = 0;
+        for roll in self.order.iter() {
+            let unit = match self.get_unit(roll.unit_id) {
+                Ok(a) => a,
+                Err(_) => continue
+            };
+            idx += 1;
+            let status = if unit.is_mid_activation() {
+                " <- acting"
+            } else if unit.overwatch().is_some() {
+                " (overwatch)"
+            } else if unit.action_points() == 0 {
+                " (done)"
+            } else {
+                ""
+            };
+            println!("\t{}. {} (initiative {}){}", idx, unit, roll, status);
+        }
+    }
+
+    /// returns the unit that should act next
+    ///
+    /// a unit that is part way through its activation keeps going, otherwise
+    /// it is the first unit in the initiative order that still has points
+    pub(crate) fn find_next_unit(&self) -> Result<&game::Unit, game::GameError> {
+        if let Some(u) = self.units.iter().find(|u| u.is_mid_activation()) {
+            return Ok(u);
+        }
+
+        for roll in self.order.iter() {
+            if let Ok(u) = self.get_unit(roll.unit_id) {
+                if u.action_points() > 0 {
+                    return Ok(u);
+                }
+            }
+        }
+
+        Err(game::GameError::NobodyLeftToAct)
+    }
+
+    /// makes sure it's `id`'s turn to act
+    fn check_turn(&self, id: u64) -> Result<(), game::GameError> {
+        match self.find_next_unit() {
+            Ok(u) if u.entity_id() == id => Ok(()),
+            _ => Err(game::GameError::NotYourTurn)
+        }
+    }
+
+    /// returns the unit with `id`
+    pub(crate) fn get_unit(&self, id: u64) -> Result<&game::Unit, game::GameError> {
+        match self.units.get(id) {
+            Some(a) => Ok(a),
+            None => Err(game::GameError::UnknownUnit(id))
+        }
+    }
+    
+    /// does the attack on behalf of the unit
+    pub(crate) fn do_attack(
+        &mut self, 
+        attacker:   u64,
+        target:     u64,
+        attack:     game::Attack 
+    ) -> Result<game::DamageStatus, game::GameError> {
+        // find the attacker and target
+        let shooter = self.get_unit(attacker)?.clone();
+        let victim = self.get_unit(target)?.clone();
+
+        // reaction shots happen on the other side's turn
+        let reaction = shooter.overwatch().is_some();
+        if !reaction {
+            self.check_turn(attacker)?;
+        }
+
+        // make sure we are within range for the attack
+        let from = shooter.position();
+        let to = victim.position();
+        let distance = self.board.distance(from, to);
+        let range = shooter.attack_range(&attack);
+        if distance > range {
+            return Err(game::GameError::OutOfRange { distance, range });
+        }
+        if !self.line_of_sight(from, to) {
+            return Err(game::GameError::NoLineOfSight);
+        }
+
+        // pay for the attack
+        if !self.spend_points(attacker, attack.cost()) {
+            return Err(game::GameError::NotEnoughPoints { cost: attack.cost(), left: shooter.action_points() });
+        }
+
+        // loaded ammo gets used up whether the attack lands or not
+        let loaded = shooter.loaded_damage();
+        if loaded > 0 {
+            self.change_unit(attacker, |u| u.set_loaded(0));
+        }
+
+        // see if the attack lands at all, smoke makes the target harder to hit
+        let buffed = shooter.accuracy() + shooter.buff(game::BuffKind::Accuracy, self.round);
+        let mut chance = buffed.min(100);
+        if self.in_smoke(to) {
+            chance = chance.saturating_sub(game::SMOKE_PENALTY);
+        }
+        if self.rng.gen_range(0..100) >= chance {
+            self.events.emit(game::Event::AttackResolved {
+                attacker_id: attacker,
+                attacker: shooter.name(),
+                target_id: target,
+                target: victim.name(),
+                attack: attack.name(),
+                damage: 0,
+                result: game::AttackResult::Missed,
+                reaction
+            });
+            return Ok(game::DamageStatus::Missed);
+        }
+        
+        // now try to do the attack
+        let damage = attack.damage()
+            + loaded
+            + shooter.buff(game::BuffKind::Damage, self.round);
+        let (status, dealt) = match self.resolve_hit(&shooter, target, &attack.name(), damage, reaction) {
+            Some(a) => a,
+            None => return Err(game::GameError::UnknownUnit(target))
+        };
+
+        // the attacker learns from the damage it actually got through
+        match status {
+            game::DamageStatus::Dead => self.grant_experience(attacker, dealt + game::KILL_XP),
+            _ => self.grant_experience(attacker, dealt)
+        }
+        Ok(status)
+    }
+
+    /// deals the damage of an attack to a unit and reports how it went.
+    /// returns if the unit survived and how much of the damage got through
+    fn resolve_hit(
+        &mut self,
+        attacker:   &game::Unit,
+        target:     u64,
+        attack:     &str,
+        damage:     u64,
+        reaction:   bool
+    ) -> Option<(game::DamageStatus, u64)> {
+        let before = self.get_unit(target).ok()?.clone();
+        let (status, dealt) = self.damage_unit(target, damage)?;
+
+        let result = match status {
+            game::DamageStatus::Dead => game::AttackResult::Killed,
+            _ => game::AttackResult::Hit { health: before.health() - dealt }
+        };
+        self.events.emit(game::Event::AttackResolved {
+            attacker_id: attacker.entity_id(),
+            attacker: attacker.name(),
+            target_id: target,
+            target: before.name(),
+            attack: attack.to_string(),
+            damage: dealt,
+            result,
+            reaction
+        });
+        if let game::DamageStatus::Dead = status {
+            self.events.emit(game::Event::UnitDied {
+                unit_id: target,
+                name: before.name(),
+                team: before.team(),
+                position: before.position()
+            });
+        }
+        Some((status, dealt))
+    }
+
+    /// deals damage to a unit, taking it off the map if it dies. returns if
+    /// it survived and how much of the damage got through
+    fn damage_unit(&mut self, target: u64, damage: u64) -> Option<(game::DamageStatus, u64)> {
+        let unit = self.units.get_mut(target)?;
+        let before = unit.clone();
+        let status = unit.deal_damage(damage);
+        let dealt = before.health() - unit.health();
+        self.history.record(game::Action::Damage {
+            before: before.clone(),
+            damage,
+            killed: matches!(status, game::DamageStatus::Dead)
+        });
+
+        if let game::DamageStatus::Dead = status {
+            // take the target off the board, it lies there until someone revives it
+            let downed = self.units.remove(target)?;
+            self.board.set_occupant(before.position(), None);
+            self.downed.push(downed);
+        }
+        Some((status, dealt))
+    }
+
+    /// returns if a tile is covered in smoke this round
+    fn in_smoke(&self, pos: game::Position) -> bool {
+        match self.board.tile(pos) {
+            Some(tile) => tile.effects().iter().any(|e| match e {
+                game::TileEffect::Smoke { last_round } => *last_round >= self.round
+            }),
+            None => false
+        }
+    }
+
+    /// returns the tiles of the map within `radius` of a tile
+    fn tiles_within(&self, center: game::Position, radius: f64) -> Vec<game::Position> {
+        // only look at the square around the circle, not the whole map
+        let reach = radius.max(0f64).floor() as usize;
+        let rows = center.x().saturating_sub(reach)..(center.x() + reach + 1).min(self.board.rows());
+        let cols = center.y().saturating_sub(reach)..(center.y() + reach + 1).min(self.board.cols());
+        rows.flat_map(|x| cols.clone().map(move |y| game::Position::new(x, y)))
+            .filter(|pos| self.board.distance(*pos, center) <= radius)
+            .collect()
+    }
+
+    /// brings a new unit into the battle, recording the change
+    ///
+    /// it sits out the rest of the round and joins the order at the top of the next one
+    pub(crate) fn spawn_unit(&mut self, mut unit: game::Unit) {
+        let pos = unit.position();
+        unit.set_points(0, 0);
+        self.board.set_occupant(pos, Some(unit.entity_id()));
+        self.units.insert(unit.clone());
+        self.history.record(game::Action::Spawned { unit: unit.clone() });
+        self.events.emit(game::Event::UnitSpawned {
+            unit_id: unit.entity_id(),
+            name: unit.name(),
+            team: unit.team(),
+            position: pos
+        });
+    }
+
+    /// changes the terrain, items or effects of a tile, recording a copy of
+    /// it from before and after
+    fn change_tile(&mut self, pos: game::Position, change: impl FnOnce(&mut game::Tile)) {
+        if let Some(tile) = self.board.tile_mut(pos) {
+            let before = tile.clone();
+            change(tile);
+            self.history.record(game::Action::Tile {
+                position: pos,
+                before,
+                after: tile.clone()
+            });
+        }
+    }
+
+    /// changes the ground of a tile, recording the change
+    pub(crate) fn set_terrain(&mut self, pos: game::Position, terrain: game::Terrain) {
+        self.change_tile(pos, |t| t.set_terrain(terrain));
+    }
+
+    /// has a unit pick up everything lying on its tile
+    fn pick_up_items(&mut self, id: u64) {
+        let unit = match self.get_unit(id) {
+            Ok(a) => a.clone(),
+            Err(_) => return
+        };
+        let pos = unit.position();
+        let items = match self.board.tile(pos) {
+            Some(tile) if !tile.items().is_empty() => tile.items().to_vec(),
+            _ => return
+        };
+
+        self.change_tile(pos, |t| {
+            t.take_items();
+        });
+        for item in items {
+            self.change_unit(id, |u| u.add_item(item.clone()));
+            self.events.emit(game::Event::ItemPickedUp {
+                unit_id: id,
+                name: unit.name(),
+                item,
+                tile: pos
+            });
+        }
+    }
+
+    /// leaves an item lying on a tile, recording the change
+    pub(crate) fn place_item(&mut self, pos: game::Position, item: game::Item) {
+        self.change_tile(pos, |t| t.add_item(item));
+    }
+
+    /// has a unit use one of its items on a tile, or the ally standing there
+    fn use_item(&mut self, user: u64, item_idx: usize, tile: game::Position) -> Result<(), game::GameError> {
+        self.check_turn(user)?;
+        let unit = self.get_unit(user)?.clone();
+        let item = match unit.items().get(item_idx) {
+            Some(a) => a.clone(),
+            None => return Err(game::GameError::NoSuchItem)
+        };
+
+        if !self.board.contains(tile) {
+            return Err(game::GameError::OutOfBounds);
+        }
+        let distance = self.board.distance(unit.position(), tile);
+        if distance > item.range() {
+            return Err(game::GameError::OutOfRange { distance, range: item.range() });
+        }
+
+        // some items only work on friendly units, and healing or loading
+        // needs someone to heal or load whatever the item is aimed at
+        let ally = self.units.iter()
+            .find(|u| u.position() == tile && u.team() == unit.team())
+            .map(|u| u.entity_id());
+        let needs_ally = matches!(item.target(), game::ItemTarget::Ally)
+            || matches!(item.effect(), game::Effect::Heal(_) | game::Effect::Load(_));
+        if needs_ally && ally.is_none() {
+            return Err(game::GameError::NeedsAlly(item.name()));
+        }
+
+        if !self.spend_points(user, item.cost()) {
+            return Err(game::GameError::NotEnoughPoints { cost: item.cost(), left: unit.action_points() });
+        }
+        self.change_unit(user, |u| {
+            u.remove_item(item_idx);
+        });
+        self.events.emit(game::Event::ItemUsed {
+            unit_id: user,
+            name: unit.name(),
+            item: item.clone(),
+            tile
+        });
+
+        match item.effect() {
+            game::Effect::Heal(hp) => {
+                let ally = ally.ok_or_else(|| game::GameError::NeedsAlly(item.name()))?;
+                self.change_unit(ally, |u| u.heal(hp));
+                self.emit_healed(ally);
+            },
+            game::Effect::Load(damage) => {
+                let ally = ally.ok_or_else(|| game::GameError::NeedsAlly(item.name()))?;
+                self.change_unit(ally, |u| u.set_loaded(u.loaded_damage() + damage));
+                self.events.emit(game::Event::BuffApplied {
+                    unit_id: ally,
+                    name: self.get_unit(ally)?.name(),
+                    kind: game::BuffKind::Damage,
+                    amount: damage,
+                    last_round: None
+                });
+            },
+            game::Effect::Blast { damage, radius } => {
+                let tiles = self.tiles_within(tile, radius);
+                let caught: Vec<game::Unit> = self.units.iter()
+                    .filter(|u| tiles.contains(&u.position()))
+                    .cloned()
+                    .collect();
+
+                // the thrower learns from hurting the enemy, not its friends
+                let mut xp = 0;
+                for target in caught {
+                    let (status, dealt) = match self.resolve_hit(&unit, target.entity_id(), &item.name(), damage, false) {
+                        Some(a) => a,
+                        None => continue
+                    };
+                    if target.team() != unit.team() {
+                        xp += dealt;
+                        if let game::DamageStatus::Dead = status {
+                            xp += game::KILL_XP;
+                        }
+                    }
+                }
+                self.grant_experience(user, xp);
+
+                // and the cover around it is gone
+                for pos in tiles {
+                    if self.board.tile(pos).is_some_and(|t| t.terrain() == game::Terrain::Wall) {
+                        self.set_terrain(pos, game::Terrain::Open);
+                    }
+                }
+            },
+            game::Effect::Smoke { radius, rounds } => {
+                let last_round = self.round + rounds.saturating_sub(1);
+                for pos in self.tiles_within(tile, radius) {
+                    self.change_tile(pos, |t| t.add_effect(game::TileEffect::Smoke { last_round }));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// returns the downed unit lying on a tile, if there is one
+    fn downed_at(&self, pos: game::Position) -> Option<(usize, game::Unit)> {
+        self.downed.iter()
+            .enumerate()
+            .rev()
+            .find(|(_, u)| u.position() == pos)
+            .map(|(idx, u)| (idx, u.clone()))
+    }
+
+    /// has a unit use one of its abilities on a friendly unit standing, or
+    /// lying downed, on a tile
+    fn use_ability(&mut self, user: u64, ability_idx: usize, tile: game::Position) -> Result<(), game::GameError> {
+        self.check_turn(user)?;
+        let unit = self.get_unit(user)?.clone();
+        let ability = match unit.abilities().get(ability_idx) {
+            Some(a) => a.clone(),
+            None => return Err(game::GameError::NoSuchAbility)
+        };
+        if ability.uses() == 0 {
+            return Err(game::GameError::NoUsesLeft(ability.name()));
+        }
+        if !self.board.contains(tile) {
+            return Err(game::GameError::OutOfBounds);
+        }
+        let distance = self.board.distance(unit.position(), tile);
+        if distance > ability.range() {
+            return Err(game::GameError::OutOfRange { distance, range: ability.range() });
+        }
+
+        // healing and buffs go to the living, reviving to the downed
+        let target = match ability.effect() {
+            game::AbilityEffect::Revive(_) => match self.downed_at(tile) {
+                Some((_, u)) if u.team() == unit.team() => {
+                    self.check_open(tile)?;
+                    u
+                },
+                _ => return Err(game::GameError::NothingToRevive(tile))
+            },
+            _ => match self.units.iter().find(|u| u.position() == tile && u.team() == unit.team()) {
+                Some(u) => u.clone(),
+                None => return Err(game::GameError::NeedsAlly(ability.name()))
+            }
+        };
+
+        if !self.spend_points(user, ability.cost()) {
+            return Err(game::GameError::NotEnoughPoints { cost: ability.cost(), left: unit.action_points() });
+        }
+        self.change_unit(user, |u| u.spend_ability(ability_idx));
+
+        match ability.effect() {
+            game::AbilityEffect::Heal(hp) => {
+                self.change_unit(target.entity_id(), |u| u.heal(hp));
+                self.emit_healed(target.entity_id());
+            },
+            game::AbilityEffect::Buff { kind, amount, rounds } => {
+                let buff = game::Buff {
+                    kind,
+                    amount,
+                    last_round: self.round + rounds.saturating_sub(1)
+                };
+                self.change_unit(target.entity_id(), |u| u.add_buff(buff));
+                self.events.emit(game::Event::BuffApplied {
+                    unit_id: target.entity_id(),
+                    name: target.name(),
+                    kind,
+                    amount,
+                    last_round: Some(buff.last_round)
+                });
+            },
+            game::AbilityEffect::Revive(hp) => {
+                let (index, before) = self.downed_at(tile).unwrap();
+                let mut after = before.clone();
+                after.revive(hp);
+                self.downed.remove(index);
+                self.units.insert(after.clone());
+                self.board.set_occupant(tile, Some(after.entity_id()));
+                self.history.record(game::Action::Revive {
+                    before,
+                    after: after.clone(),
+                    index
+                });
+                self.events.emit(game::Event::UnitRevived {
+                    unit_id: after.entity_id(),
+                    name: after.name(),
+                    health: after.health()
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// reports a unit's hp after it was healed
+    fn emit_healed(&mut self, id: u64) {
+        if let Ok(unit) = self.get_unit(id) {
+            self.events.emit(game::Event::UnitHealed {
+                unit_id: id,
+                name: unit.name(),
+                health: unit.health(),
+                max_health: unit.max_health()
+            });
+        }
+    }
+
+    /// has a unit hand one of its items to an adjacent ally
+    fn give_item(&mut self, giver: u64, item_idx: usize, receiver: u64) -> Result<(), game::GameError> {
+        self.check_turn(giver)?;
+        let from = self.get_unit(giver)?.clone();
+        let to = self.get_unit(receiver)?;
+        let item = match from.items().get(item_idx) {
+            Some(a) => a.clone(),
+            None => return Err(game::GameError::NoSuchItem)
+        };
+        if from.team() != to.team() || giver == receiver {
+            return Err(game::GameError::NeedsAlly(item.name()));
+        }
+        let distance = self.board.distance(from.position(), to.position());
+        if distance > 1f64 {
+            return Err(game::GameError::OutOfRange { distance, range: 1f64 });
+        }
+        if !self.spend_points(giver, game::GIVE_COST) {
+            return Err(game::GameError::NotEnoughPoints { cost: game::GIVE_COST, left: from.action_points() });
+        }
+
+        self.change_unit(giver, |u| {
+            u.remove_item(item_idx);
+        });
+        self.change_unit(receiver, |u| u.add_item(item));
+        Ok(())
+    }
+
+    /// gives a unit experience, recording the change
+    fn grant_experience(&mut self, id: u64, amount: u64) {
+        let skills = self.skills.clone();
+        let mut reached = Vec::new();
+        self.change_unit(id, |u| reached = u.gain_experience(amount, &skills));
+
+        let unit = match self.get_unit(id) {
+            Ok(a) => a.clone(),
+            Err(_) => return
+        };
+        let last = reached.last().copied();
+        for level in reached {
+            self.events.emit(game::Event::LevelUp {
+                unit_id: id,
+                name: unit.name(),
+                level,
+                bonuses: skills.bonuses(level).iter().map(|b| (b.stat, b.amount)).collect(),
+                // only bring up promotions once, after the last level
+                can_promote: Some(level) == last && !skills.available(&unit).is_empty()
+            });
+        }
+    }
+
+    /// has a unit take a promotion from the skill tree, recording the change
+    fn promote_unit(&mut self, id: u64, promotion: &game::Promotion) {
+        self.change_unit(id, |u| u.promote(promotion));
+    }
+
+    /// changes a unit's experience, stats or attacks, recording a copy of it
+    /// from before and after
+    fn change_unit(&mut self, id: u64, change: impl FnOnce(&mut game::Unit)) {
+        if let Some(unit) = self.units.get_mut(id) {
+            let before = unit.clone();
+            change(unit);
+            self.history.record(game::Action::Changed {
+                before,
+                after: unit.clone()
+            });
+        }
+    }
+
+    /// returns the part of the map that gets drawn, around wherever the
+    /// player has scrolled to or else the unit that's up
+    fn viewport(&self) -> game::Viewport {
+        let placing = self.deployment.as_ref().and_then(|d| Some(d.rows(d.placing()?)));
+        let center = match (self.view, placing) {
+            (Some(a), _) => a,
+            // the middle of the zone the team deploying is placing units in
+            (None, Some(rows)) => game::Position::new((rows.start + rows.end) / 2, self.board.cols() / 2),
+            (None, None) => match self.find_next_unit() {
+                Ok(u) => u.position(),
+                Err(_) => game::Position::new(self.board.rows() / 2, self.board.cols() / 2)
+            }
+        };
+        game::Viewport::centered(&self.board, center)
+    }
+
+    /// returns the glyph a tile is drawn with
+    fn glyph(&self, pos: game::Position) -> char {
+        let tile = match self.board.tile(pos) {
+            Some(a) => a,
+            None => return ' '
+        };
+        // whoever is standing on the tile hides what's under them
+        let unit = tile.occupant()
+            .and_then(|id| self.get_unit(id).ok())
+            .filter(|u| !self.hidden(u));
+        match (unit, tile.terrain()) {
+            (Some(u), _) => match u.team() {
+                game::Team::Redfor => 'R',
+                game::Team::Bluefor => 'B'
+            },
+            (None, game::Terrain::Wall) => '^',
+            (None, game::Terrain::Open) => match self.objective_glyph(pos) {
+                Some(c) => c,
+                None if self.downed_at(pos).is_some() => 'x',
+                None if !tile.items().is_empty() => '+',
+                None if self.in_smoke(pos) => '~',
+                None if self.deployment.as_ref()
+                    .is_some_and(|d| d.placing().is_some_and(|t| d.in_zone(t, pos))) => '-',
+                None => ' '
+            }
+        }
+    }
+
+    /// prints the grid to the screen
+    ///
+    /// a map too big for the screen only has the part around the active
+    /// unit drawn, followed by a minimap of the whole thing
+    fn print_grid(&self) {
+        let view = self.viewport();
+
+        // loop over each row in view, top of the screen first
+        for row in (view.top()..view.top() + view.rows()).rev() {
+            let cells = (view.left()..view.left() + view.cols())
+                .map(|cell| self.glyph(game::Position::new(row, cell)));
+            match self.board.topology() {
+                game::Topology::Square => {
+                    print!("|");
+                    for glyph in cells {
+                        print!("{}|", glyph);
+                    }
+                },
+                // each row sits half a hex right of the one below it, and
+                // open ground gets a dot so the hexes can be told apart
+                game::Topology::Hex => {
+                    print!("{}", " ".repeat(row - view.top()));
+                    for glyph in cells {
+                        print!(" {}", if glyph == ' ' { '.' } else { glyph });
+                    }
+                }
+            }
+            println!();
+        }
+
+        if !view.shows_all(&self.board) {
+            println!(
+                "Viewing rows {}-{} and columns {}-{} of a {} by {} map, `view` to look around",
+                view.top(),
+                view.top() + view.rows() - 1,
+                view.left(),
+                view.left() + view.cols() - 1,
+                self.board.cols(),
+                self.board.rows()
+            );
+            self.print_minimap(&view);
+        }
+
+        // say who owns the control points, since units can stand on them
+        if !self.scenario.control_points().is_empty() {
+            let points: Vec<String> = self.scenario.control_points()
+                .iter()
+                .map(|p| p.to_string())
+                .collect();
+            println!(
+                "Control points: {} | Score: Redfor {} / Bluefor {}",
+                points.join(", "),
+                self.scenario.score(&self.units, game::Team::Redfor),
+                self.scenario.score(&self.units, game::Team::Bluefor)
+            );
+        }
+    }
+
+    /// prints the whole map squeezed down, with a block for every few tiles
+    fn print_minimap(&self, view: &game::Viewport) {
+        let minimap = game::Minimap::new(&self.board);
+        let mut walls = vec![vec![0; minimap.cols()]; minimap.rows()];
+        let mut teams = vec![vec![(false, false); minimap.cols()]; minimap.rows()];
+        for pos in self.board.positions() {
+            let (r, c) = minimap.cell(pos);
+            if self.board.tile(pos).is_some_and(|t| t.terrain() == game::Terrain::Wall) {
+                walls[r][c] += 1;
+            }
+        }
+        for unit in self.units.iter().filter(|u| !self.hidden(u)) {
+            let (r, c) = minimap.cell(unit.position());
+            match unit.team() {
+                game::Team::Redfor => teams[r][c].0 = true,
+                game::Team::Bluefor => teams[r][c].1 = true
+            }
+        }
+
+        println!(
+            "Minimap, each cell is {} by {} tiles (R/B units, * both, ^ mostly cover, : in view):",
+            minimap.block(),
+            minimap.block()
+        );
+        let area = minimap.block() * minimap.block();
+        for r in (0..minimap.rows()).rev() {
+            print!("\t");
+            for c in 0..minimap.cols() {
+                let glyph = match teams[r][c] {
+                    (true, true) => '*',
+                    (true, false) => 'R',
+                    (false, true) => 'B',
+                    _ if walls[r][c] * 2 > area => '^',
+                    _ if minimap.in_view(view, r, c) => ':',
+                    _ => '.'
+                };
+                print!("{}", glyph);
+            }
+            println!();
+        }
+    }
+
+    /// attempts to move a unit one tile, returning `Interrupted` if it was
+    /// shot at on the way
+    pub(crate) fn move_unit(&mut self, unit_id: u64, mov: game::Movement) -> Result<game::ErrorOut, game::GameError> {
+        self.check_turn(unit_id)?;
+        let unit = self.get_unit(unit_id)?.clone();
+        let curr_pos = unit.position();
+
+        // bounds check it
+        if !self.board.directions().contains(&mov) {
+            return Err(game::GameError::BadDirection(mov));
+        }
+        let newpos = match self.board.step(curr_pos, mov) {
+            Some(a) => a,
+            None => return Err(game::GameError::OutOfBounds)
+        };
+        if self.board.corner_blocked(curr_pos, mov) {
+            return Err(game::GameError::CornerBlocked(mov));
+        }
+
+        // note this catches both terrain and friendly units in the way
+        self.check_open(newpos)?;
+
+        // moving costs action points, diagonals going by how the map measures range
+        let diagonal = self.board.is_diagonal(mov);
+        let cost = if diagonal {
+            game::MOVE_COST * self.board.metric().diagonal_moves(unit.diagonal_steps() + 1)
+        } else {
+            game::MOVE_COST
+        };
+        if !self.spend_points(unit_id, cost) {
+            return Err(game::GameError::NotEnoughPoints { cost, left: unit.action_points() });
+        }
+        if diagonal {
+            self.change_unit(unit_id, |u| u.set_diagonal_steps(u.diagonal_steps() + 1));
+        }
+
+        // position is valid, update internal stuff
+        self.relocate(unit_id, curr_pos, newpos);
+        self.history.record(game::Action::Move {
+            unit_id,
+            from: curr_pos,
+            to: newpos
+        });
+        self.events.emit(game::Event::UnitMoved {
+            unit_id,
+            name: unit.name(),
+            from: curr_pos,
+            to: newpos
+        });
+        self.pick_up_items(unit_id);
+
+        // moving into view of an enemy on overwatch draws fire
+        if self.reaction_fire(unit_id, curr_pos) {
+            return Ok(game::ErrorOut::Interrupted);
+        }
+
+        Ok(game::ErrorOut::Success)
+    }
+
+    /// starts the deployment phase for the given units, lifting them off the
+    /// map to be placed again. returns false and leaves them lined up if
+    /// nobody needs placing or either team's zone is short of room
+    pub(crate) fn begin_deployment(&mut self, mode: game::DeployMode, ids: &[u64]) -> bool {
+        let units: Vec<(u64, game::Team)> = ids.iter()
+            .filter_map(|id| self.get_unit(*id).ok())
+            .map(|u| (u.entity_id(), u.team()))
+            .collect();
+        let deployment = game::Deployment::new(mode, &self.board, &units);
+        if deployment.next().is_none() {
+            return false;
+        }
+        for team in [game::Team::Redfor, game::Team::Bluefor] {
+            // tiles the team's own units are standing on free up once they're lifted
+            let room = deployment.zone(&self.board, team)
+                .into_iter()
+                .filter_map(|pos| self.board.tile(pos))
+                .filter(|t| t.terrain().passable())
+                .filter(|t| t.occupant().is_none_or(|id| deployment.is_waiting(id)))
+                .count();
+            if room < deployment.waiting_for(team).len() {
+                self.events.emit(game::Event::Message {
+                    text: format!("{}'s deployment zone is too small, lining up instead", team)
+                });
+                return false;
+            }
+        }
+
+        for (id, _) in &units {
+            let pos = self.get_unit(*id).unwrap().position();
+            if self.board.occupant(pos) == Some(*id) {
+                self.board.set_occupant(pos, None);
+            }
+        }
+        self.deployment = Some(deployment);
+        true
+    }
+
+    /// places a unit waiting to deploy on a tile in its team's zone, ending
+    /// the deployment phase once everyone is down
+    pub(crate) fn deploy_unit(&mut self, id: u64, pos: game::Position) -> Result<(), game::GameError> {
+        let team = self.get_unit(id)?.team();
+        let deployment = match &self.deployment {
+            Some(a) if a.is_waiting(id) => a,
+            _ => return Err(game::GameError::NotYourTurn)
+        };
+        if !self.board.contains(pos) {
+            return Err(game::GameError::OutOfBounds);
+        }
+        if !deployment.in_zone(team, pos) {
+            return Err(game::GameError::OutsideDeployment(pos));
+        }
+        self.check_open(pos)?;
+
+        self.board.set_occupant(pos, Some(id));
+        if let Some(unit) = self.units.get_mut(id) {
+            unit.move_unit(pos);
+        }
+        if let Some(deployment) = &mut self.deployment {
+            deployment.placed(id);
+            if deployment.next().is_none() {
+                self.deployment = None;
+            }
+        }
+        Ok(())
+    }
+
+    /// returns if a unit is kept off the screen while the teams deploy,
+    /// either because it hasn't been placed or the other team is placing
+    /// in secret
+    fn hidden(&self, unit: &game::Unit) -> bool {
+        self.deployment.as_ref()
+            .is_some_and(|d| d.is_waiting(unit.entity_id()) || d.hides(unit.team()))
+    }
+
+    /// makes sure a unit could stand on a tile
+    fn check_open(&self, pos: game::Position) -> Result<(), game::GameError> {
+        let tile = match self.board.tile(pos) {
+            Some(a) => a,
+            None => return Err(game::GameError::OutOfBounds)
+        };
+        if !tile.terrain().passable() {
+            return Err(game::GameError::BlockedByTerrain(pos));
+        }
+        match tile.occupant() {
+            Some(id) => Err(game::GameError::OccupiedByUnit {
+                name: self.get_unit(id).map(|u| u.name()).unwrap_or_default(),
+                position: pos
+            }),
+            None => Ok(())
+        }
+    }
+
+    /// moves a unit between two tiles without any checks
+    fn relocate(&mut self, unit_id: u64, from: game::Position, to: game::Position) {
+        self.board.set_occupant(from, None);
+        self.board.set_occupant(to, Some(unit_id));
+        if let Some(unit) = self.units.get_mut(unit_id) {
+            unit.move_unit(to);
+        }
+    }
+
+    /// applies an action from the history, either forwards (redo) or backwards (undo)
+    fn apply(&mut self, action: &game::Action, forward: bool) {
+        match action {
+            game::Action::Move { unit_id, from, to } => {
+                if forward {
+                    self.relocate(*unit_id, *from, *to);
+                } else {
+                    self.relocate(*unit_id, *to, *from);
+                }
+            },
+            game::Action::Damage { before, damage, killed } => {
+                let id = before.entity_id();
+                let pos = before.position();
+                if forward {
+                    if let Some(unit) = self.units.get_mut(id) {
+                        unit.deal_damage(*damage);
+                    }
+                    if *killed {
+                        if let Some(downed) = self.units.remove(id) {
+                            self.board.set_occupant(pos, None);
+                            self.downed.push(downed);
+                        }
+                    }
+                } else if *killed {
+                    // bring the dead unit back where it was
+                    self.downed.pop();
+                    self.units.insert(before.clone());
+                    self.board.set_occupant(pos, Some(id));
+                } else {
+                    self.units.insert(before.clone());
+                }
+            },
+            game::Action::Revive { before, after, index } => {
+                let pos = before.position();
+                if forward {
+                    self.downed.remove(*index);
+                    self.units.insert(after.clone());
+                    self.board.set_occupant(pos, Some(after.entity_id()));
+                } else {
+                    self.units.remove(after.entity_id());
+                    self.board.set_occupant(pos, None);
+                    self.downed.insert(*index, before.clone());
+                }
+            },
+            game::Action::Spawned { unit } => {
+                let pos = unit.position();
+                if forward {
+                    self.units.insert(unit.clone());
+                    self.board.set_occupant(pos, Some(unit.entity_id()));
+                } else {
+                    self.units.remove(unit.entity_id());
+                    self.board.set_occupant(pos, None);
+                }
+            },
+            game::Action::Changed { before, after } => {
+                let changed = if forward { after } else { before };
+                if let Some(unit) = self.units.get_mut(changed.entity_id()) {
+                    *unit = changed.clone();
+                }
+            },
+            game::Action::Points { unit_id, before, after } => {
+                let (action_points, banked_points) = if forward { *after } else { *before };
+                if let Some(unit) = self.units.get_mut(*unit_id) {
+                    unit.set_points(action_points, banked_points);
+                }
+            },
+            game::Action::TurnEnded => {
+                if forward {
+                    self.turns += 1;
+                } else {
+                    self.turns -= 1;
+                }
+            },
+            game::Action::NewRound { before } => {
+                for unit in self.units.iter_mut() {
+                    let old = before.iter().find(|(id, _)| *id == unit.entity_id());
+                    match (forward, old) {
+                        (true, _) => unit.set_points(unit.max_action_points(), unit.banked_points()),
+                        (false, Some((_, points))) => unit.set_points(*points, unit.banked_points()),
+                        (false, None) => ()
+                    }
+                }
+                if forward {
+                    self.round += 1;
+                } else {
+                    self.round -= 1;
+                }
+            },
+            game::Action::Reorder { before, after } => {
+                if forward {
+                    self.order = after.clone();
+                } else {
+                    self.order = before.clone();
+                }
+            },
+            game::Action::Tile { position, before, after } => {
+                let changed = if forward { after } else { before };
+                if let Some(tile) = self.board.tile_mut(*position) {
+                    tile.restore(changed);
+                }
+            },
+            game::Action::Objectives { before, after } => {
+                if forward {
+                    self.scenario = after.clone();
+                } else {
+                    self.scenario = before.clone();
+                }
+            }
+        }
+    }
+
+    /// closes the current action in the history so it can be undone
+    pub(crate) fn commit_action(&mut self) {
+        if self.history.commit() {
+            self.events.emit(game::Event::Committed);
+        }
+    }
+
+    /// reverts the last completed action, returning if there was one
+    fn undo_action(&mut self) -> bool {
+        // throw away anything half done first
+        for action in self.history.rollback().iter().rev() {
+            self.apply(action, false);
+        }
+
+        match self.history.undo() {
+            Some(step) => {
+                for action in step.iter().rev() {
+                    self.apply(action, false);
+                }
+                self.events.emit(game::Event::Undone);
+                true
+            },
+            None => false
+        }
+    }
+
+    /// replays the last undone action, returning if there was one
+    fn redo_action(&mut self) -> bool {
+        // throw away anything half done first, like the start of the
+        // activation that followed the undo
+        for action in self.history.rollback().iter().rev() {
+            self.apply(action, false);
+        }
+
+        match self.history.redo() {
+            Some(step) => {
+                for action in step.iter() {
+                    self.apply(action, true);
+                }
+                self.events.emit(game::Event::Redone);
+                true
+            },
+            None => false
+        }
+    }
+}
+
+/// how a match ended, `None` if it stopped before anyone won
+pub(crate) struct MatchResult {
+    pub(crate) outcome: Option<game::Outcome>
+}
+
+/// has the teams place the units that start the battle, one at a time in
+/// the order `mode` says, unless they're lining up
+pub(crate) fn deploy(g: &mut Game, mode: game::DeployMode, units: &[u64]) {
+    if mode == game::DeployMode::Lineup || !g.begin_deployment(mode, units) {
+        return;
+    }
+    println!("[+] Place your units on the tiles marked -, or `auto` to leave it to the AI");
+
+    let mut last: Option<game::Team> = None;
+    while let Some(id) = g.deployment.as_ref().and_then(|d| d.next()) {
+        let unit = match g.get_unit(id) {
+            Ok(a) => a.clone(),
+            Err(_) => return
+        };
+        // hand over between teams so neither sees where the other went
+        if mode == game::DeployMode::Hidden && last != Some(unit.team()) {
+            if let Some(team) = last {
+                println!("[+] {} is done, hand over to {}", team, unit.team());
+            }
+            println!("[+] {} deploys without seeing the other team", unit.team());
+        }
+        last = Some(unit.team());
+        g.view = None;
+        g.print_grid();
+
+        loop {
+            let ustr = match g.input(&format!("[{}] {} (deploying) > ", unit.team(), unit.name())) {
+                Some(a) => a,
+                None => {
+                    println!("[-] Out of input, deploying the rest automatically");
+                    ai::deploy(g, None);
+                    return;
+                }
+            };
+            let rcode = match command::parse(&ustr, &g.commands()) {
+                Ok(parsed) => (parsed.command.action)(g, id, &parsed.args),
+                Err(command::ParseError::Empty) => continue,
+                Err(e) => {
+                    println!("[-] {}", e);
+                    continue
+                }
+            };
+            if let game::ErrorOut::Success = rcode {
+                break;
+            }
+        }
+    }
+    println!("[+] Both teams are deployed");
+}
+
+/// builds the scenario's victory conditions from the command line options
+pub(crate) fn setup_scenario(g: &mut Game, opts: &config::Options) -> Result<(), String> {
+    if let Some(rounds) = opts.turn_limit {
+        g.scenario.add(game::Condition::TurnLimit { rounds });
+    }
+
+    for (tiles, rounds) in &opts.hold {
+        g.clear_tiles(tiles)?;
+        g.scenario.add(game::Condition::HoldTiles {
+            tiles: tiles.clone(),
+            rounds: *rounds
+        });
+    }
+
+    for team in &opts.vip {
+        let vip = match g.units.iter().find(|u| u.team() == *team) {
+            Some(u) => u.clone(),
+            None => return Err(format!("{} has no units to make a VIP", team))
+        };
+        g.events.emit(game::Event::Message {
+            text: format!("{} is the {} VIP", vip.name(), team)
+        });
+        g.scenario.add(game::Condition::KillVip {
+            unit_id: vip.entity_id(),
+            team: *team
+        });
+    }
+
+    for (team, zone) in &opts.extract {
+        g.clear_tiles(zone)?;
+        g.scenario.add(game::Condition::Extraction {
+            team: *team,
+            zone: zone.clone()
+        });
+    }
+
+    g.clear_tiles(&opts.control)?;
+    for point in &opts.control {
+        g.scenario.add_control_point(*point);
+    }
+    if let Some(radius) = opts.control_radius {
+        g.scenario.set_control_radius(radius);
+    }
+    if let Some(points) = opts.score_limit {
+        if opts.control.is_empty() {
+            return Err("--score-limit needs control points to score from".to_string());
+        }
+        g.scenario.add(game::Condition::ScoreLimit { points });
+    }
+
+    // the mission goes last so its setup sees the finished map
+    if let Some(path) = &opts.mission {
+        mission::Mission::load(path, g)?;
+    }
+
+    Ok(())
+}
+
+/// plays the match until a team wins or the players stop
+pub(crate) fn play(g: &mut Game) -> MatchResult {
+    let game_commands = g.commands();
+    let mut result = MatchResult {
+        outcome: None
+    };
+
+    println!(
+        "[+] Every unit acts once per round with {} action points",
+        g.stats.action_points
+    );
+
+    // see who wins ties in the initiative order
+    match g.seize_initiative() {
+        game::Team::Bluefor => println!("[B] BLUEFOR has seized the initiative!"),
+        game::Team::Redfor => println!("[R] REDFOR has seized the initiative!")
+    }
+
+    // begin main game loop
+    loop {
+        if g.round_over() {
+            // score the round that just finished
+            if g.round > 0 {
+                g.end_round();
+                g.run_mission();
+                if let Some(outcome) = g.check_victory(true) {
+                    g.events.emit(game::Event::GameOver { outcome: outcome.clone() });
+                    result.outcome = Some(outcome);
+                    return result;
+                }
+            }
+
+            // hand out fresh action points once everyone is spent
+            println!("[+] Top of the initiative order");
+            g.new_round();
+            g.print_order();
+        }
+
+        // the map follows whoever is up next
+        g.view = None;
+        g.print_grid();
+        if let Some(outcome) = g.check_victory(false) {
+            g.events.emit(game::Event::GameOver { outcome: outcome.clone() });
+            result.outcome = Some(outcome);
+            return result;
+        }
+
+        // find the player's units
+        let s1 = match g.find_next_unit() {
+            Ok(a) => a.clone(),
+            Err(e) => {
+                println!("[-] {}", e);
+                return result;
+            }
+        };
+        if s1.action_points() == s1.max_action_points() {
+            g.start_activation(s1.entity_id());
+            g.run_mission();
+        }
+
+        // get the player's input 
+        while let Ok(unit) = g.get_unit(s1.entity_id()) {
+            let ustr = match g.input(&format!(
+                "[{}] {} ({} AP) > ",
+                unit.team(),
+                unit.name(),
+                unit.action_points()
+            )) {
+                Some(a) => a,
+                None => {
+                    println!("[-] Out of input, ending the game");
+                    return result;
+                }
+            };
+
+            // look for the command
+            let rcode = match command::parse(&ustr, &game_commands) {
+                Ok(parsed) => (parsed.command.action)(g, s1.entity_id(), &parsed.args),
+                Err(command::ParseError::Empty) => continue,
+                Err(e) => {
+                    println!("[-] {}", e);
+                    continue
+                }
+            };
+            g.run_mission();
+
+            // deterine command outcome
+            match rcode {
+                game::ErrorOut::Success => {
+                    // the activation is over once the unit is out of points
+                    let spent = match g.get_unit(s1.entity_id()) {
+                        Ok(u) => u.action_points() == 0,
+                        Err(_) => true
+                    };
+                    if spent {
+                        g.end_turn();
+                    }
+                    g.commit_action();
+
+                    // any action could have won the game
+                    if let Some(outcome) = g.check_victory(false) {
+                        g.events.emit(game::Event::GameOver { outcome: outcome.clone() });
+                        result.outcome = Some(outcome);
+                        return result;
+                    }
+                    if spent {
+                        break;
+                    }
+                },
+                game::ErrorOut::SuccessIncomplete => continue,
+                // the command has already said what it couldn't find
+                game::ErrorOut::NotFound => continue,
+                // the history changed, so work out who is up all over again
+                game::ErrorOut::Undone => break,
+                // the unit gave up its place in the order
+                game::ErrorOut::Yielded => {
+                    g.commit_action();
+                    break
+                },
+                game::ErrorOut::Aborted => {
+                    let outcome = game::Outcome::Draw("ended by the players".to_string());
+                    g.events.emit(game::Event::GameOver { outcome: outcome.clone() });
+                    result.outcome = Some(outcome);
+                    return result;
+                },
+                _ => println!("[-] Unexpected error...")
+            }
+        }
+    }
+}
+
+/// builds the end of match report
+pub(crate) fn report(g: &Game, result: &MatchResult) -> String {
+    let mut out = String::new();
+    match &result.outcome {
+        Some(game::Outcome::Winner(team, reason)) => {
+            out.push_str(&format!("winner: {}\n", team));
+            out.push_str(&format!("reason: {}\n", reason));
+        },
+        Some(game::Outcome::Draw(reason)) => {
+            out.push_str("winner: none\n");
+            out.push_str(&format!("reason: {}\n", reason));
+        },
+        None => {
+            out.push_str("winner: none\n");
+            out.push_str("reason: unfinished\n");
+        }
+    }
+    out.push_str(&format!("rounds: {}\n", g.round));
+    out.push_str(&format!("turns: {}\n", g.turns));
+    out.push_str("survivors:\n");
+    for unit in g.units() {
+        out.push_str(&format!("\t{} at {}\n", unit, unit.position()));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// a one on one game on a small open map, started and with the id of
+    /// whoever acts first
+    fn small_game() -> (Game, u64) {
+        let map = game::MapSettings {
+            width: 4,
+            height: 6,
+            cover: 0,
+            ..game::MapSettings::default()
+        };
+        let mut g = Game::new(&map, 1, StdRng::seed_from_u64(1));
+        g.new_round();
+        let id = g.find_next_unit().unwrap().entity_id();
+        (g, id)
+    }
+
+    #[test]
+    fn healing_an_empty_tile_needs_an_ally() {
+        let (mut g, id) = small_game();
+        let stim = game::Item::new("Stim".to_string(), game::Effect::Heal(10), game::ItemTarget::Tile, 5.0, 1);
+        g.change_unit(id, |u| u.set_items(vec![stim]));
+        let points = g.get_unit(id).unwrap().action_points();
+
+        let empty = game::Position::new(2, 2);
+        assert_eq!(g.use_item(id, 0, empty), Err(game::GameError::NeedsAlly("Stim".to_string())));
+        // nothing was spent on it
+        let unit = g.get_unit(id).unwrap();
+        assert_eq!(unit.action_points(), points);
+        assert_eq!(unit.items().len(), 1);
+    }
+}
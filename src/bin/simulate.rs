@@ -0,0 +1,258 @@
+// plays lots of ai vs ai matches without a terminal and reports how they went
+
+use rand::prelude::*;
+
+use crap_tactica::{
+    ai,
+    config,
+    Game,
+    Outcome,
+    Team
+};
+
+const USAGE: &str = "Usage: simulate [options]
+
+Options:
+    --matches <number>          how many matches to play, defaults to 1000
+    --threads <number>          how many matches to play at once, defaults to the number of cores
+    --size <tiles>              the width and height of the map, defaults to 16
+    --max-rounds <rounds>       call a match unfinished after this many rounds, defaults to 100
+    --damage <attack>=<dmg>     change the damage of an attack for every unit, e.g. Shoot=35
+
+Every match is set up from crap_tactica's options too, e.g. --seed for the
+first match's seed (each match after uses the next one), --units, --health,
+--attacks, --deploy, --army or --config. The ai deploys both teams. Options
+for playing at the terminal, like --script or --undo-consent, are ignored.";
+
+/// z score for a 95% confidence interval
+const Z_95: f64 = 1.96;
+
+/// options given on the command line
+struct Options {
+    matches:    u64,
+    threads:    usize,
+    max_rounds: u64,
+    damage:     Vec<(String, u64)>,
+    /// how every match is set up, the same as for crap_tactica
+    setup:      config::Options
+}
+
+/// parses a number given to an option
+fn parse_number<T: std::str::FromStr>(flag: &str, arg: Option<String>) -> Result<T, String> {
+    match arg {
+        Some(a) => match a.parse::<T>() {
+            Ok(n) => Ok(n),
+            Err(_) => Err(format!("{} needs a number, got '{}'", flag, a))
+        },
+        None => Err(format!("{} needs a number", flag))
+    }
+}
+
+/// parses the command line options, leaving anything that isn't one of ours
+/// to the match options
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut opts = Options {
+        matches:    1000,
+        threads:    std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+        max_rounds: 100,
+        damage:     Vec::new(),
+        setup:      config::Options::default()
+    };
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--matches" => opts.matches = parse_number(&arg, args.next())?,
+            "--threads" => opts.threads = parse_number(&arg, args.next())?,
+            "--size" => {
+                let size = parse_number(&arg, args.next())?;
+                opts.setup.width = Some(size);
+                opts.setup.height = Some(size);
+            },
+            "--max-rounds" => opts.max_rounds = parse_number(&arg, args.next())?,
+            "--damage" => {
+                let a = match args.next() {
+                    Some(a) => a,
+                    None => return Err("--damage needs an attack and its damage, e.g. Shoot=35".to_string())
+                };
+                let (name, dmg) = match a.split_once('=') {
+                    Some(a) => a,
+                    None => return Err(format!("'{}' should look like Shoot=35", a))
+                };
+                opts.damage.push((name.to_string(), parse_number("--damage", Some(dmg.to_string()))?));
+            },
+            _ => {
+                if !opts.setup.parse_arg(&arg, &mut args)? {
+                    return Err(format!("Unknown option '{}'", arg));
+                }
+            }
+        }
+    }
+
+    if opts.threads == 0 {
+        return Err("--threads needs to be at least 1".to_string());
+    }
+    Ok(opts)
+}
+
+/// works out how every match is set up, with the command line over the
+/// config file and both over the defaults
+fn load_settings(opts: &Options) -> Result<config::Settings, String> {
+    let file = match &opts.setup.config {
+        Some(path) => Some(config::load(path)?),
+        None => None
+    };
+    let settings = config::Settings::new(&opts.setup, file.as_ref())?;
+    if settings.options.campaign.is_some() {
+        return Err("Campaigns can't be simulated, only single matches".to_string());
+    }
+    if settings.options.mission.is_some() {
+        return Err("Missions can't be simulated, the ai would deploy the units they add".to_string());
+    }
+    Ok(settings)
+}
+
+/// plays every match with a seed in `seeds`
+fn play_matches(opts: &Options, settings: &config::Settings, seeds: Vec<u64>) -> Result<Vec<ai::Summary>, String> {
+    seeds.into_iter().map(|seed| {
+        let mut g = Game::from_settings(settings, StdRng::seed_from_u64(seed))?;
+        for (attack, damage) in opts.damage.iter() {
+            g.set_attack_damage(attack, *damage);
+        }
+        ai::deploy_all(&mut g, settings.deploy);
+        Ok(ai::play_match(&mut g, opts.max_rounds))
+    }).collect()
+}
+
+/// returns the 95% wilson score interval for `hits` out of `n`
+fn wilson(hits: u64, n: u64) -> (f64, f64) {
+    if n == 0 {
+        return (0f64, 0f64);
+    }
+    let n = n as f64;
+    let p = hits as f64 / n;
+    let z2 = Z_95 * Z_95;
+    let center = (p + z2 / (2f64 * n)) / (1f64 + z2 / n);
+    let spread = Z_95 * (p * (1f64 - p) / n + z2 / (4f64 * n * n)).sqrt() / (1f64 + z2 / n);
+    (center - spread, center + spread)
+}
+
+/// formats `hits` out of `n` as a rate with its confidence interval
+fn rate(hits: u64, n: u64) -> String {
+    let (low, high) = wilson(hits, n);
+    let p = if n == 0 { 0f64 } else { hits as f64 / n as f64 };
+    format!(
+        "{} ({:.1}%, 95% CI {:.1}% - {:.1}%)",
+        hits,
+        p * 100f64,
+        low * 100f64,
+        high * 100f64
+    )
+}
+
+/// returns the mean of `values` and the half width of its 95% confidence interval
+fn mean(values: &[u64]) -> (f64, f64) {
+    if values.is_empty() {
+        return (0f64, 0f64);
+    }
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<u64>() as f64 / n;
+    if values.len() < 2 {
+        return (mean, 0f64);
+    }
+    let variance = values.iter().map(|v| (*v as f64 - mean).powi(2)).sum::<f64>() / (n - 1f64);
+    (mean, Z_95 * (variance / n).sqrt())
+}
+
+/// counts how many of the matches a team won
+fn wins(results: &[&ai::Summary], team: Team) -> u64 {
+    results.iter()
+        .filter(|r| matches!(&r.outcome, Some(Outcome::Winner(t, _)) if *t == team))
+        .count() as u64
+}
+
+/// prints the win rates of a set of matches
+fn print_rates(results: &[&ai::Summary], indent: &str) {
+    let n = results.len() as u64;
+    let draws = results.iter().filter(|r| matches!(r.outcome, Some(Outcome::Draw(_)))).count() as u64;
+    let unfinished = results.iter().filter(|r| r.outcome.is_none()).count() as u64;
+    println!("{}Bluefor wins: {}", indent, rate(wins(results, Team::Bluefor), n));
+    println!("{}Redfor wins: {}", indent, rate(wins(results, Team::Redfor), n));
+    println!("{}Draws: {}", indent, rate(draws, n));
+    if unfinished > 0 {
+        println!("{}Unfinished: {}", indent, rate(unfinished, n));
+    }
+}
+
+fn main() {
+    let opts = match parse_args(std::env::args().skip(1)) {
+        Ok(a) => a,
+        Err(e) => {
+            println!("[-] {}", e);
+            println!("{}", USAGE);
+            std::process::exit(1);
+        }
+    };
+
+    let settings = match load_settings(&opts) {
+        Ok(a) => a,
+        Err(e) => {
+            println!("[-] {}", e);
+            std::process::exit(1);
+        }
+    };
+    if settings.options.print_config {
+        print!("{}", settings.to_config());
+        return;
+    }
+    let seed = settings.options.seed.unwrap_or(0);
+
+    println!(
+        "[+] Playing {} matches on {} thread(s), seeds {} to {}",
+        opts.matches,
+        opts.threads,
+        seed,
+        seed + opts.matches.saturating_sub(1)
+    );
+
+    // deal the seeds out between the threads
+    let mut batches: Vec<Vec<u64>> = vec![Vec::new(); opts.threads];
+    for i in 0..opts.matches {
+        batches[(i % opts.threads as u64) as usize].push(seed + i);
+    }
+    let results: Result<Vec<Vec<ai::Summary>>, String> = std::thread::scope(|s| {
+        let handles: Vec<_> = batches.into_iter()
+            .map(|seeds| {
+                let (opts, settings) = (&opts, &settings);
+                s.spawn(move || play_matches(opts, settings, seeds))
+            })
+            .collect();
+        handles.into_iter()
+            .map(|h| h.join().expect("a simulation thread panicked"))
+            .collect()
+    });
+    let results: Vec<ai::Summary> = match results {
+        Ok(a) => a.into_iter().flatten().collect(),
+        Err(e) => {
+            println!("[-] {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let all: Vec<&ai::Summary> = results.iter().collect();
+    println!("Results:");
+    print_rates(&all, "\t");
+
+    for first in [Team::Bluefor, Team::Redfor] {
+        let side: Vec<&ai::Summary> = results.iter().filter(|r| r.first == first).collect();
+        println!("When {} moves first ({} matches):", first, side.len());
+        print_rates(&side, "\t");
+    }
+
+    let rounds: Vec<u64> = results.iter().map(|r| r.rounds).collect();
+    let turns: Vec<u64> = results.iter().map(|r| r.turns).collect();
+    let (rounds, rounds_ci) = mean(&rounds);
+    let (turns, turns_ci) = mean(&turns);
+    println!("Match length:");
+    println!("\t{:.2} rounds on average (95% CI +/- {:.2})", rounds, rounds_ci);
+    println!("\t{:.2} turns on average (95% CI +/- {:.2})", turns, turns_ci);
+}
@@ -0,0 +1,143 @@
+// the army builder, where each side spends its points on units before the match
+
+use crate::game;
+use crate::input::InputSource;
+
+/// what the builder's commands do
+const BUILDER_HELP: &str = "Army builder commands:
+	list                         Shows the archetypes and loadouts and what they cost
+	add <archetype> <loadout> [name]  Buys a unit, by name or number from the list
+	remove <number>              Takes back a unit
+	show                         Shows the army so far
+	save <file>                  Saves the army to use again
+	load <file>                  Replaces the army with a saved one
+	done                         Takes the army into the match
+	help                         Shows these commands";
+
+/// prints the archetypes and loadouts on the list
+fn print_list(list: &game::ArmyList) {
+    println!("Archetypes:");
+    for (idx, archetype) in list.archetypes().iter().enumerate() {
+        println!("\t{}. {}", idx + 1, archetype);
+    }
+    println!("Loadouts:");
+    for (idx, loadout) in list.loadouts().iter().enumerate() {
+        println!("\t{}. {}", idx + 1, loadout);
+    }
+}
+
+/// prints the units bought so far and what they cost
+fn print_army(list: &game::ArmyList, army: &game::Army) {
+    if army.picks().is_empty() {
+        println!("The army has no units yet");
+        return;
+    }
+    println!("Army:");
+    for (idx, pick) in army.picks().iter().enumerate() {
+        let points = list.archetype(&pick.archetype).map(|a| a.points).unwrap_or(0)
+            + list.loadout(&pick.loadout).map(|l| l.points).unwrap_or(0);
+        println!("\t{}. {} - {} with the {} loadout, {} pts", idx + 1, pick.name, pick.archetype, pick.loadout, points);
+    }
+}
+
+/// finds an entry of the list by its number or name
+fn find<'a, T>(items: &'a [T], arg: &str, name: impl Fn(&T) -> &str) -> Option<&'a T> {
+    match arg.parse::<usize>() {
+        Ok(n) => items.get(n.checked_sub(1)?),
+        Err(_) => items.iter().find(|i| name(i).eq_ignore_ascii_case(arg))
+    }
+}
+
+/// has a side spend `budget` points on its army, starting from `army`.
+/// returns `None` if input runs out first
+pub fn build(
+    list:       &game::ArmyList,
+    team:       game::Team,
+    budget:     u64,
+    mut army:   game::Army,
+    input:      &mut InputSource
+) -> Option<game::Army> {
+    println!("[+] {} has {} points to spend on its army, `help` for how", team, budget);
+    print_list(list);
+
+    loop {
+        // anything that isn't on the list has been caught when loading
+        let spent = army.cost(list).unwrap_or(0);
+        let ustr = input.read(&format!(
+            "[{}] Army ({}/{} pts left) > ",
+            team,
+            budget.saturating_sub(spent),
+            budget
+        ))?;
+        let words: Vec<&str> = ustr.split_whitespace().collect();
+        match words.as_slice() {
+            [] => continue,
+            ["list"] => print_list(list),
+            ["show"] => print_army(list, &army),
+            ["help"] => println!("{}", BUILDER_HELP),
+            ["add", archetype, loadout, name @ ..] => {
+                let archetype = match find(list.archetypes(), archetype, |a| &a.name) {
+                    Some(a) => a,
+                    None => {
+                        println!("[-] There is no archetype '{}'", archetype);
+                        continue
+                    }
+                };
+                let loadout = match find(list.loadouts(), loadout, |l| &l.name) {
+                    Some(a) => a,
+                    None => {
+                        println!("[-] There is no loadout '{}'", loadout);
+                        continue
+                    }
+                };
+                let name = match name.join(" ") {
+                    n if n.is_empty() => {
+                        let count = army.picks().iter().filter(|p| p.archetype == archetype.name).count();
+                        format!("{} #{}", archetype.name, count + 1)
+                    },
+                    n => n
+                };
+
+                let mut bought = army.clone();
+                bought.add(game::Pick {
+                    name: name.clone(),
+                    archetype: archetype.name.clone(),
+                    loadout: loadout.name.clone()
+                });
+                match bought.validate(list, budget) {
+                    Ok(_) => {
+                        army = bought;
+                        println!("[+] {} joins the army", name);
+                    },
+                    Err(e) => println!("[-] {}", e)
+                }
+            },
+            ["remove", n] => match n.parse::<usize>().ok().filter(|n| (1..=army.picks().len()).contains(n)) {
+                Some(n) => println!("[+] {} leaves the army", army.remove(n - 1).name),
+                None => println!("[-] There is no unit {} in the army", n)
+            },
+            ["save", path] => match std::fs::write(path, army.to_text()) {
+                Ok(_) => println!("[+] Army saved to {}", path),
+                Err(e) => println!("[-] Failed to write army '{}': {}", path, e)
+            },
+            ["load", path] => match load_army(path).and_then(|a| a.validate(list, budget).map(|_| a)) {
+                Ok(a) => {
+                    army = a;
+                    print_army(list, &army);
+                },
+                Err(e) => println!("[-] {}", e)
+            },
+            ["done"] if army.picks().is_empty() => println!("[-] The army needs at least one unit"),
+            ["done"] => return Some(army),
+            _ => println!("[-] Unknown army builder command '{}', `help` for the list", ustr.trim())
+        }
+    }
+}
+
+/// loads a saved army from a file
+pub fn load_army(path: &str) -> Result<game::Army, String> {
+    match std::fs::read_to_string(path) {
+        Ok(a) => game::Army::parse(&a).map_err(|e| format!("{} in army '{}'", e, path)),
+        Err(e) => Err(format!("Failed to read army '{}': {}", path, e))
+    }
+}
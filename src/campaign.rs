@@ -0,0 +1,278 @@
+// defines campaigns, a series of battles where the surviving units carry over
+
+use rand::prelude::*;
+
+use crate::after_action::AfterAction;
+use crate::battle::{
+    default_squad,
+    deploy,
+    play,
+    setup_scenario,
+    Game
+};
+use crate::cli::raise_army;
+use crate::config::{
+    load_skills,
+    parse_args,
+    Options,
+    Settings
+};
+use crate::console::Console;
+use crate::game;
+use crate::input::InputSource;
+
+/// a single battle of a campaign
+struct Battle {
+    name:       String,
+    options:    Options
+}
+
+/// a campaign loaded from its definition file
+///
+/// the file is made of lines like these, with `#` starting a comment:
+///
+/// ```text
+/// name Operation Billy
+/// team bluefor
+/// battle Breakout: --extract b:0x0,0x1 --turn-limit 8
+/// battle Hold the line: --hold 7x7,7x8:3
+/// ```
+///
+/// where `team` is the side whose survivors carry over, and each battle takes
+/// the same scenario options as the command line
+struct Campaign {
+    name:       String,
+    team:       game::Team,
+    battles:    Vec<Battle>
+}
+
+impl Campaign {
+    /// loads a campaign from its definition file
+    fn load(path: &str) -> Result<Self, String> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(a) => a,
+            Err(e) => return Err(format!("Failed to read campaign '{}': {}", path, e))
+        };
+
+        let mut campaign = Campaign {
+            name: path.to_string(),
+            team: game::Team::Bluefor,
+            battles: Vec::new()
+        };
+        for line in contents.lines().map(|l| l.trim()) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (key, rest) = line.split_once(' ').unwrap_or((line, ""));
+            match key {
+                "name" => campaign.name = rest.trim().to_string(),
+                "team" => campaign.team = rest.trim().parse::<game::Team>()?,
+                "battle" => {
+                    let (name, options) = rest.split_once(':').unwrap_or((rest, ""));
+                    let args = options.split_whitespace().map(|a| a.to_string());
+                    let options = match parse_args(args) {
+                        Ok(a) => a,
+                        Err(e) => return Err(format!("Battle '{}': {}", name.trim(), e))
+                    };
+                    campaign.battles.push(Battle {
+                        name: name.trim().to_string(),
+                        options
+                    });
+                },
+                _ => return Err(format!("Bad campaign line '{}'", line))
+            }
+        }
+
+        if campaign.battles.is_empty() {
+            return Err(format!("Campaign '{}' has no battles", path));
+        }
+        Ok(campaign)
+    }
+}
+
+/// how far a campaign has gotten, kept in its save file
+struct Progress {
+    campaign:   String,
+    next:       usize,
+    results:    Vec<String>,
+    roster:     game::Roster
+}
+
+impl Progress {
+    /// starts a campaign from scratch with the team's first units
+    fn new(campaign: &str, team: game::Team, units: Vec<game::Unit>) -> Self {
+        Progress {
+            campaign: campaign.to_string(),
+            next: 0,
+            results: Vec::new(),
+            roster: game::Roster::new(team, &units.into_iter().collect())
+        }
+    }
+
+    /// loads the progress from a save file
+    fn load(path: &str) -> Result<Self, String> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(a) => a,
+            Err(e) => return Err(format!("Failed to read save '{}': {}", path, e))
+        };
+
+        let mut campaign = None;
+        let mut next = 0;
+        let mut results = Vec::new();
+        let mut roster_lines = Vec::new();
+        for line in contents.lines().filter(|l| !l.trim().is_empty()) {
+            match line.split_once('|') {
+                Some(("campaign", a)) => campaign = Some(a.to_string()),
+                Some(("next", a)) => next = match a.parse::<usize>() {
+                    Ok(n) => n,
+                    Err(_) => return Err(format!("Bad save line '{}'", line))
+                },
+                Some(("result", a)) => results.push(a.to_string()),
+                _ => roster_lines.push(line.to_string())
+            }
+        }
+
+        match campaign {
+            Some(campaign) => Ok(Progress {
+                campaign,
+                next,
+                results,
+                roster: game::Roster::from_lines(&roster_lines)?
+            }),
+            None => Err(format!("Save '{}' doesn't name its campaign", path))
+        }
+    }
+
+    /// writes the progress to a save file
+    fn save(&self, path: &str) -> Result<(), String> {
+        let mut lines = vec![
+            format!("campaign|{}", self.campaign),
+            format!("next|{}", self.next)
+        ];
+        for result in &self.results {
+            lines.push(format!("result|{}", result));
+        }
+        lines.append(&mut self.roster.to_lines()?);
+
+        match std::fs::write(path, lines.join("\n") + "\n") {
+            Ok(_) => Ok(()),
+            Err(e) => Err(format!("Failed to write save '{}': {}", path, e))
+        }
+    }
+}
+
+/// prints the roster screen shown between battles
+fn print_roster(campaign: &Campaign, progress: &Progress) {
+    println!("==== {} ====", campaign.name);
+    for (idx, battle) in campaign.battles.iter().enumerate() {
+        let status = match progress.results.get(idx) {
+            Some(r) => r.clone(),
+            None if idx == progress.next => "up next".to_string(),
+            None => "".to_string()
+        };
+        println!("\t{}. {} {}", idx + 1, battle.name, status);
+    }
+
+    println!("{} roster:", progress.roster.team());
+    for unit in progress.roster.units() {
+        let attacks: Vec<String> = unit.attacks()
+            .iter()
+            .filter(|a| a.name() != "Cancel")
+            .map(|a| a.name())
+            .collect();
+        println!("\t{} ({})", unit, attacks.join(", "));
+    }
+}
+
+/// plays a campaign from its definition file, picking up from the save file if there is one
+pub fn run(path: &str, save: &str, opts: &Options, mut input: InputSource) -> Result<(), String> {
+    let campaign = Campaign::load(path)?;
+    let skills = load_skills(&opts.skills)?;
+    let mut progress = if std::path::Path::new(save).exists() {
+        let progress = Progress::load(save)?;
+        if progress.campaign != path {
+            return Err(format!("Save '{}' belongs to campaign '{}'", save, progress.campaign));
+        }
+        println!("[+] Resuming campaign from {}", save);
+        progress
+    } else {
+        // the team's own army if it has one, the default squad otherwise
+        match raise_army(&Settings::new(opts, None)?, campaign.team, 0, &mut input)? {
+            Some(units) => Progress::new(path, campaign.team, units),
+            None => return Ok(())
+        }
+    };
+
+    while progress.next < campaign.battles.len() {
+        if progress.roster.units().is_empty() {
+            println!("[-] Every unit on the roster is dead. The campaign is lost!");
+            return Ok(());
+        }
+
+        print_roster(&campaign, &progress);
+        let ustr = match input.read("Press enter to start the next battle, or 'quit' to stop > ") {
+            Some(a) => a,
+            None => return Ok(())
+        };
+        if ustr.trim().eq_ignore_ascii_case("quit") {
+            println!("[+] Campaign saved to {}", save);
+            return Ok(());
+        }
+
+        // our survivors against a fresh enemy squad
+        let battle = &campaign.battles[progress.next];
+        let settings = Settings::new(&battle.options, Some(opts))?;
+        println!("[+] Battle {}: {}", progress.next + 1, battle.name);
+        let mut units = progress.roster.deploy(0, &settings.stats);
+        units.append(&mut default_squad(
+            campaign.team.other_team(),
+            units.len() as u64,
+            settings.squad_size,
+            &settings.stats
+        ));
+
+        let seed = battle.options.seed.or(opts.seed.map(|s| s + progress.next as u64));
+        let rng = match seed {
+            Some(s) => StdRng::seed_from_u64(s),
+            None => StdRng::from_entropy()
+        };
+        let mut g = Game::with_units(&settings.map, rng, units);
+        g.events.subscribe(Box::new(Console));
+        g.stats = settings.stats.clone();
+        g.undo_consent = settings.undo_consent;
+        g.initiative_roll = !settings.fixed_initiative;
+        g.skills = match &battle.options.skills {
+            Some(_) => load_skills(&battle.options.skills)?,
+            None => skills.clone()
+        };
+        let starting: Vec<u64> = g.units().map(|u| u.entity_id()).collect();
+        setup_scenario(&mut g, &battle.options)?;
+
+        g.set_input(input);
+        deploy(&mut g, settings.deploy, &starting);
+        let after_action = AfterAction::new(&g.units);
+        g.events.subscribe(after_action.recorder());
+
+        let result = play(&mut g);
+        input = g.take_input();
+        print!("{}", after_action.report());
+
+        // a battle that ran out of input isn't over, so don't count it
+        let outcome = match result.outcome {
+            Some(a) => a,
+            None => return Ok(())
+        };
+        progress.results.push(match outcome {
+            game::Outcome::Winner(team, reason) => format!("{} won ({})", team, reason),
+            game::Outcome::Draw(reason) => format!("draw ({})", reason)
+        });
+        progress.roster = game::Roster::new(campaign.team, &g.units);
+        progress.next += 1;
+        progress.save(save)?;
+    }
+
+    print_roster(&campaign, &progress);
+    println!("[+] The campaign is complete!");
+    Ok(())
+}
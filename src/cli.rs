@@ -0,0 +1,218 @@
+// the terminal game's command line: the options it takes and how it sets up
+// and plays a match, or a campaign, from them
+
+use rand::prelude::*;
+
+use crate::after_action;
+use crate::battle::{
+    deploy,
+    play,
+    report,
+    Game
+};
+use crate::builder;
+use crate::campaign;
+use crate::config;
+use crate::console;
+use crate::event_log;
+use crate::game;
+use crate::input::InputSource;
+
+/// the command line usage
+const USAGE: &str = "Usage: crap_tactica [options]
+    --script <file>             read commands from a file
+    --echo                      print prompts and commands read from the script
+    --report <file>             write the end of match report to a file
+    --seed <number>             make the map and dice rolls repeatable
+    --width <tiles>             how many columns across the map is, defaults to 16
+    --height <tiles>            how many rows up the map is, defaults to 16
+    --grid <square|hex>         the shape of the map's tiles, defaults to square
+    --metric <metric>           how square maps measure range: euclidean (default), manhattan,
+                                chebyshev or octile
+    --diagonals <rule>          diagonal moves on square maps: off (default), free, no-squeeze
+                                or no-corners
+    --cover <percent>           how much of the map between the teams is cover, defaults to 20
+    --units <count>             how many units each default squad has, defaults to 3
+    --health <hp>               the health default units start with, defaults to 100
+    --initiative <number>       the initiative default units roll with, defaults to 10
+    --accuracy <percent>        the chance every unit's attacks hit, defaults to 85
+    --action-points <points>    the action points every unit gets each round, defaults to 4
+    --attacks <attacks>         the attacks default units have, as <name>:<damage>:<range>:<cost>,
+                                e.g. \"Fight:50:1:2, Shoot:30:10:3\"
+    --deploy <mode>             how the teams set up: lineup (default), alternate or hidden
+    --army <team>:<file>        field a saved army for the team instead of the default squad
+    --build-army                have each team buy its army before the match
+    --budget <points>           how many points each army can cost, defaults to the army list's
+    --army-list <file>          load the archetypes and loadouts armies are bought from
+    --undo-consent              make the other player agree to undos
+    --fixed-initiative          don't roll for initiative each round
+    --turn-limit <rounds>       end the match after this many rounds, highest score wins
+    --hold <tiles>:<rounds>     win by holding every tile for this many rounds, e.g. 7x7,8x8:2
+    --vip <team>                the team loses if its first unit is killed
+    --extract <team>:<tiles>    the team wins by reaching any of the tiles, e.g. b:0x0,0x1
+    --control <tiles>           add control points that score each round for whoever holds them
+    --control-radius <tiles>    how close units must be to capture a control point
+    --score-limit <points>      the first team to this many points wins
+    --campaign <file>           play a campaign, a series of battles with the same units
+    --save <file>               where the campaign is saved, defaults to <campaign>.save
+    --skills <file>             load the levels and promotions units earn from a skill tree file
+    --event-log <file>          write everything that happens in the match to a file
+    --mission <file>            run a mission script that sets up the battle and reacts to it
+    --export <file>             write the after-action report to a .json or .csv file
+    --config <file>             read any of these options from a file, one per line without
+                                the dashes, e.g. `width 24`. the command line wins
+    --no-<switch>               turn off a switch the config file turned on, e.g.
+                                --no-undo-consent
+    --print-config              print the settings in effect, as a config file, and stop";
+
+/// puts together a team's units, numbered from `first_id`, from its saved
+/// army or the army builder, or else the default squad. `None` if the
+/// builder ran out of input
+pub(crate) fn raise_army(
+    settings:   &config::Settings,
+    team:       game::Team,
+    first_id:   u64,
+    input:      &mut InputSource
+) -> Result<Option<Vec<game::Unit>>, String> {
+    if !settings.build_army {
+        return settings.army(team, first_id).map(Some);
+    }
+
+    // the builder starts from the saved army, if there is one
+    let saved = match settings.options.army.iter().find(|(t, _)| *t == team) {
+        Some((_, path)) => builder::load_army(path)?,
+        None => game::Army::new()
+    };
+    let (list, budget) = (&settings.army_list, settings.budget);
+    let army = match builder::build(list, team, budget, saved, input) {
+        Some(a) => a,
+        None => return Ok(None)
+    };
+    army.validate(list, budget).map_err(|e| format!("{}'s army won't do: {}", team, e))?;
+    army.units(list, team, first_id, &settings.stats).map(Some)
+}
+
+/// runs the game from the command line
+pub fn run() {
+    let cli = match config::parse_args(std::env::args().skip(1)) {
+        Ok(a) => a,
+        Err(e) => {
+            println!("[-] {}", e);
+            println!("{}", USAGE);
+            std::process::exit(1);
+        }
+    };
+
+    // the command line goes over the config file, which goes over the defaults
+    let config = match cli.config.as_deref().map(config::load) {
+        Some(Ok(a)) => Some(a),
+        Some(Err(e)) => {
+            println!("[-] {}", e);
+            std::process::exit(1);
+        },
+        None => None
+    };
+    let settings = match config::Settings::new(&cli, config.as_ref()) {
+        Ok(a) => a,
+        Err(e) => {
+            println!("[-] {}", e);
+            std::process::exit(1);
+        }
+    };
+    if cli.print_config {
+        print!("{}", settings.to_config());
+        return;
+    }
+    let opts = &settings.options;
+
+    // work out where input is coming from
+    let mut input = match &opts.script {
+        Some(path) => match InputSource::from_script(path, settings.echo) {
+            Ok(a) => a,
+            Err(e) => {
+                println!("[-] {}", e);
+                std::process::exit(1);
+            }
+        },
+        None => InputSource::stdin()
+    };
+
+    // campaigns run their own series of games
+    if let Some(path) = &opts.campaign {
+        let save = opts.save.clone().unwrap_or(format!("{}.save", path));
+        if let Err(e) = campaign::run(path, &save, opts, input) {
+            println!("[-] {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    // bluefor gets the first ids, then redfor
+    let mut units = Vec::new();
+    for team in [game::Team::Bluefor, game::Team::Redfor] {
+        match raise_army(&settings, team, units.len() as u64, &mut input) {
+            Ok(Some(mut a)) => units.append(&mut a),
+            Ok(None) => {
+                println!("[-] Out of input, ending the game");
+                return;
+            },
+            Err(e) => {
+                println!("[-] {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+    let rng = match opts.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy()
+    };
+    // create our game struct
+    let mut g = Game::with_units(&settings.map, rng, units);
+
+    g.events.subscribe(Box::new(console::Console));
+    if let Some(path) = &opts.event_log {
+        match event_log::EventLog::create(path) {
+            Ok(log) => g.events.subscribe(Box::new(log)),
+            Err(e) => {
+                println!("[-] {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+    // only the units the battle starts with get deployed, not any the mission adds
+    let starting: Vec<u64> = g.units().map(|u| u.entity_id()).collect();
+    if let Err(e) = g.apply_settings(&settings) {
+        println!("[-] {}", e);
+        std::process::exit(1);
+    }
+
+    g.set_input(input);
+    deploy(&mut g, settings.deploy, &starting);
+
+    // start keeping track once the battle is set up
+    let after_action = after_action::AfterAction::new(&g.units);
+    g.events.subscribe(after_action.recorder());
+
+    let result = play(&mut g);
+
+    let aar = after_action.report();
+    print!("{}", aar);
+    if let Some(path) = &opts.export {
+        if let Err(e) = aar.export(path) {
+            println!("[-] {}", e);
+            std::process::exit(1);
+        }
+    }
+
+    // scripted runs always get a report so scenarios can be checked
+    if opts.script.is_some() || opts.report.is_some() {
+        let out = report(&g, &result);
+        print!("{}", out);
+        if let Some(path) = &opts.report {
+            if let Err(e) = std::fs::write(path, out) {
+                println!("[-] Failed to write report '{}': {}", path, e);
+                std::process::exit(1);
+            }
+        }
+    }
+}
@@ -0,0 +1,314 @@
+// defines our player commands and the parser that turns a line of input into one
+
+use crate::game;
+use crate::Game;
+
+/// the kind of value an argument expects
+#[derive(Clone, Copy, PartialEq)]
+pub enum ArgKind {
+    Direction,
+    Tile,
+    Number,
+    Attack,
+    Target,
+    Command,
+    Promotion,
+    Item,
+    Ability,
+    TargetOrTile,
+    DirectionOrTile,
+}
+
+/// display format implementation
+impl std::fmt::Display for ArgKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let printable = match self {
+            ArgKind::Direction => "direction",
+            ArgKind::Tile => "tile",
+            ArgKind::Number => "number",
+            ArgKind::Attack => "attack name or number",
+            ArgKind::Target => "target number",
+            ArgKind::Command => "command",
+            ArgKind::Promotion => "promotion name or number",
+            ArgKind::Item => "item name or number",
+            ArgKind::Ability => "ability name or number",
+            ArgKind::TargetOrTile => "target number or tile",
+            ArgKind::DirectionOrTile => "direction or tile",
+        };
+
+        write!(f, "{}", printable)
+    }
+}
+
+/// describes a single argument a command accepts
+#[derive(Clone)]
+pub struct CommandArg {
+    pub name:       String,
+    pub kind:       ArgKind,
+    pub optional:   bool,
+}
+
+impl CommandArg {
+    /// creates a new optional argument
+    pub fn optional(name: &str, kind: ArgKind) -> Self {
+        CommandArg {
+            name: name.to_string(),
+            kind,
+            optional: true
+        }
+    }
+}
+
+/// defines a command usable in the game
+#[derive(Clone)]
+pub struct Command {
+    pub cmd:        String,
+    pub aliases:    Vec<String>,
+    pub args:       Vec<CommandArg>,
+    pub help:       String,
+    pub action:     fn (game: &mut Game, unit_id: u64, args: &[String]) -> game::ErrorOut,
+}
+
+impl Command {
+    /// creates a new command with no aliases or arguments
+    pub fn new(
+        cmd:    &str,
+        help:   &str,
+        action: fn (game: &mut Game, unit_id: u64, args: &[String]) -> game::ErrorOut
+    ) -> Self {
+        Command {
+            cmd: cmd.to_string(),
+            aliases: Vec::new(),
+            args: Vec::new(),
+            help: help.to_string(),
+            action
+        }
+    }
+
+    /// adds aliases to the command
+    pub fn with_aliases(mut self, aliases: &[&str]) -> Self {
+        for alias in aliases {
+            self.aliases.push(alias.to_string());
+        }
+        self
+    }
+
+    /// adds an argument to the command's schema
+    pub fn with_arg(mut self, arg: CommandArg) -> Self {
+        self.args.push(arg);
+        self
+    }
+
+    /// returns if the word names this command, ignoring case
+    pub fn matches(&self, word: &str) -> bool {
+        let word = word.to_lowercase();
+        self.cmd == word || self.aliases.contains(&word)
+    }
+
+    /// returns the usage string of the command, e.g. `move [direction] [count]`
+    pub fn usage(&self) -> String {
+        let mut usage = self.cmd.clone();
+        for arg in &self.args {
+            if arg.optional {
+                usage.push_str(&format!(" [{}]", arg.name));
+            } else {
+                usage.push_str(&format!(" <{}>", arg.name));
+            }
+        }
+        usage
+    }
+}
+
+/// reasons a line of input could not be turned into a command
+pub enum ParseError {
+    Empty,
+    Unknown(String, Option<String>),
+    TooManyArgs(String),
+    MissingArg(String),
+    BadArg(String, String),
+}
+
+/// display format implementation
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ParseError::Empty => write!(f, "No command given"),
+            ParseError::Unknown(word, Some(suggestion)) =>
+                write!(f, "Command not found '{}'. Did you mean '{}'?", word, suggestion),
+            ParseError::Unknown(word, None) =>
+                write!(f, "Command not found '{}'", word),
+            ParseError::TooManyArgs(usage) =>
+                write!(f, "Too many arguments. Usage: {}", usage),
+            ParseError::MissingArg(usage) =>
+                write!(f, "Missing argument. Usage: {}", usage),
+            ParseError::BadArg(arg, kind) =>
+                write!(f, "'{}' is not a valid {}", arg, kind),
+        }
+    }
+}
+
+/// a command that has been matched and had its arguments split out
+pub struct ParsedCommand {
+    pub command:    Command,
+    pub args:       Vec<String>,
+}
+
+/// parses a line of input against the list of known commands
+pub fn parse(line: &str, commands: &[Command]) -> Result<ParsedCommand, ParseError> {
+    let mut words = line.split_whitespace();
+    let word = match words.next() {
+        Some(w) => w,
+        None => return Err(ParseError::Empty)
+    };
+    let args: Vec<String> = words.map(|w| w.to_lowercase()).collect();
+
+    // look for the command by name or alias
+    let command = match commands.iter().find(|c| c.matches(word)) {
+        Some(c) => c.clone(),
+        None => return Err(ParseError::Unknown(word.to_string(), suggest(word, commands)))
+    };
+
+    // check the arguments against the schema
+    if args.len() > command.args.len() {
+        return Err(ParseError::TooManyArgs(command.usage()));
+    }
+    for (idx, schema) in command.args.iter().enumerate() {
+        let arg = match args.get(idx) {
+            Some(a) => a,
+            None => {
+                if !schema.optional {
+                    return Err(ParseError::MissingArg(command.usage()));
+                }
+                continue
+            }
+        };
+
+        let valid = match schema.kind {
+            ArgKind::Direction => arg.parse::<game::Movement>().is_ok(),
+            ArgKind::Tile => arg.parse::<game::Position>().is_ok(),
+            ArgKind::Number | ArgKind::Target => arg.parse::<usize>().is_ok(),
+            ArgKind::TargetOrTile =>
+                arg.parse::<usize>().is_ok() || arg.parse::<game::Position>().is_ok(),
+            ArgKind::DirectionOrTile =>
+                arg.parse::<game::Movement>().is_ok() || arg.parse::<game::Position>().is_ok(),
+            ArgKind::Attack | ArgKind::Command | ArgKind::Promotion | ArgKind::Item
+                | ArgKind::Ability => true,
+        };
+        if !valid {
+            return Err(ParseError::BadArg(arg.clone(), schema.kind.to_string()));
+        }
+    }
+
+    Ok(ParsedCommand { command, args })
+}
+
+/// finds the closest command name or alias to a mistyped word
+fn suggest(word: &str, commands: &[Command]) -> Option<String> {
+    let word = word.to_lowercase();
+    let mut best: Option<(usize, String)> = None;
+
+    let names = commands.iter().flat_map(|c| std::iter::once(&c.cmd).chain(c.aliases.iter()));
+    for name in names {
+        let dist = edit_distance(&word, name);
+        // only suggest things that are actually close, which rules out
+        // most of the one and two letter aliases
+        if dist > 2 || dist >= name.len() {
+            continue;
+        }
+        let better = match &best {
+            Some((d, _)) => dist < *d,
+            None => true
+        };
+        if better {
+            best = Some((dist, name.clone()));
+        }
+    }
+
+    best.map(|(_, name)| name)
+}
+
+/// computes the levenshtein distance between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+
+    for i in 1..=a.len() {
+        let mut curr = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let cost = if a[i-1] == b[j-1] { 0 } else { 1 };
+            curr[j] = (prev[j] + 1)         // deletion
+                .min(curr[j-1] + 1)         // insertion
+                .min(prev[j-1] + cost);     // substitution
+        }
+        prev = curr;
+    }
+
+    prev[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nothing(_: &mut Game, _: u64, _: &[String]) -> game::ErrorOut {
+        game::ErrorOut::Success
+    }
+
+    /// a couple of commands shaped like the real ones
+    fn commands() -> Vec<Command> {
+        vec![
+            Command::new("move", "moves", nothing)
+                .with_aliases(&["m", "mv"])
+                .with_arg(CommandArg::optional("direction", ArgKind::Direction))
+                .with_arg(CommandArg::optional("count", ArgKind::Number)),
+            Command::new("overwatch", "watches", nothing)
+                .with_aliases(&["ow", "watch"])
+        ]
+    }
+
+    /// the message a line fails to parse with
+    fn error(line: &str) -> String {
+        match parse(line, &commands()) {
+            Ok(_) => panic!("'{}' parsed", line),
+            Err(e) => e.to_string()
+        }
+    }
+
+    #[test]
+    fn commands_match_by_name_or_alias_in_any_case() {
+        let parsed = parse("MV Up 2", &commands()).ok().unwrap();
+        assert_eq!(parsed.command.cmd, "move");
+        assert_eq!(parsed.args, vec!["up", "2"]);
+        assert_eq!(parse("watch", &commands()).ok().unwrap().command.cmd, "overwatch");
+    }
+
+    #[test]
+    fn arguments_are_checked_against_the_schema() {
+        assert_eq!(error("  "), "No command given");
+        assert_eq!(error("move up 2 3"), "Too many arguments. Usage: move [direction] [count]");
+        assert_eq!(error("move up lots"), "'lots' is not a valid number");
+        assert_eq!(error("move sideways"), "'sideways' is not a valid direction");
+    }
+
+    #[test]
+    fn typos_suggest_the_closest_name_or_alias() {
+        assert_eq!(error("mvoe"), "Command not found 'mvoe'. Did you mean 'move'?");
+        assert_eq!(error("wach"), "Command not found 'wach'. Did you mean 'watch'?");
+        assert_eq!(error("overwach"), "Command not found 'overwach'. Did you mean 'overwatch'?");
+    }
+
+    #[test]
+    fn short_aliases_and_far_off_words_suggest_nothing() {
+        assert_eq!(error("x"), "Command not found 'x'");
+        assert_eq!(error("fireball"), "Command not found 'fireball'");
+    }
+
+    #[test]
+    fn edit_distance_counts_single_letter_changes() {
+        assert_eq!(edit_distance("move", "move"), 0);
+        assert_eq!(edit_distance("mvoe", "move"), 2);
+        assert_eq!(edit_distance("wach", "watch"), 1);
+        assert_eq!(edit_distance("", "ow"), 2);
+    }
+}
@@ -0,0 +1,616 @@
+// defines the options a match is set up with and the settings they add up
+// to, layered from the defaults, a config file and the command line
+
+use crate::after_action;
+use crate::builder;
+use crate::battle::default_squad;
+use crate::game;
+
+/// how many units each team's default squad has unless told otherwise
+pub const DEFAULT_SQUAD_SIZE: u64 = 3;
+
+/// options given on the command line, or in a config file
+///
+/// switches are `None` unless turned on or off, so the command line can
+/// turn off one the config file turned on
+#[derive(Clone, Default)]
+pub struct Options {
+    pub script:              Option<String>,
+    pub echo:                Option<bool>,
+    pub report:              Option<String>,
+    pub seed:                Option<u64>,
+    pub width:               Option<u64>,
+    pub height:              Option<u64>,
+    pub grid:                Option<game::Topology>,
+    pub metric:              Option<game::Metric>,
+    pub diagonals:           Option<game::Diagonals>,
+    pub cover:               Option<u64>,
+    pub units:               Option<u64>,
+    pub health:              Option<u64>,
+    pub initiative:          Option<u64>,
+    pub accuracy:            Option<u64>,
+    pub action_points:       Option<u64>,
+    pub attacks:             Option<Vec<game::Attack>>,
+    pub deploy:              Option<game::DeployMode>,
+    pub army:                Vec<(game::Team, String)>,
+    pub build_army:          Option<bool>,
+    pub budget:              Option<u64>,
+    pub army_list:           Option<String>,
+    pub undo_consent:        Option<bool>,
+    pub fixed_initiative:    Option<bool>,
+    pub turn_limit:          Option<u64>,
+    pub hold:                Vec<(Vec<game::Position>, u64)>,
+    pub vip:                 Vec<game::Team>,
+    pub extract:             Vec<(game::Team, Vec<game::Position>)>,
+    pub control:             Vec<game::Position>,
+    pub control_radius:      Option<f64>,
+    pub score_limit:         Option<u64>,
+    pub campaign:            Option<String>,
+    pub save:                Option<String>,
+    pub skills:              Option<String>,
+    pub event_log:           Option<String>,
+    pub mission:             Option<String>,
+    pub export:              Option<String>,
+    pub config:              Option<String>,
+    pub print_config:        bool
+}
+
+/// parses a comma separated list of tiles, e.g. `3x4,5x6`
+fn parse_tiles(s: &str) -> Result<Vec<game::Position>, String> {
+    s.split(',').map(|t| t.parse::<game::Position>()).collect()
+}
+
+impl Options {
+    /// reads the option `arg`, taking its value from `args` if it needs one.
+    /// `false` if `arg` isn't one of the match options
+    pub fn parse_arg(&mut self, arg: &str, args: &mut impl Iterator<Item = String>) -> Result<bool, String> {
+        match arg {
+            "--script" => match args.next() {
+                Some(a) => self.script = Some(a),
+                None => return Err("--script needs a file".to_string())
+            },
+            "--echo" => self.echo = Some(true),
+            "--no-echo" => self.echo = Some(false),
+            "--undo-consent" => self.undo_consent = Some(true),
+            "--no-undo-consent" => self.undo_consent = Some(false),
+            "--fixed-initiative" => self.fixed_initiative = Some(true),
+            "--no-fixed-initiative" => self.fixed_initiative = Some(false),
+            "--report" => match args.next() {
+                Some(a) => self.report = Some(a),
+                None => return Err("--report needs a file".to_string())
+            },
+            "--seed" => match args.next().map(|a| a.parse::<u64>()) {
+                Some(Ok(a)) => self.seed = Some(a),
+                _ => return Err("--seed needs a number".to_string())
+            },
+            "--width" => match args.next().map(|a| a.parse::<u64>()) {
+                Some(Ok(a)) => self.width = Some(a),
+                _ => return Err("--width needs a number of tiles".to_string())
+            },
+            "--height" => match args.next().map(|a| a.parse::<u64>()) {
+                Some(Ok(a)) => self.height = Some(a),
+                _ => return Err("--height needs a number of tiles".to_string())
+            },
+            "--grid" => match args.next() {
+                Some(a) => self.grid = Some(a.parse::<game::Topology>()?),
+                None => return Err("--grid needs square or hex".to_string())
+            },
+            "--metric" => match args.next() {
+                Some(a) => self.metric = Some(a.parse::<game::Metric>()?),
+                None => return Err("--metric needs euclidean, manhattan, chebyshev or octile".to_string())
+            },
+            "--diagonals" => match args.next() {
+                Some(a) => self.diagonals = Some(a.parse::<game::Diagonals>()?),
+                None => return Err("--diagonals needs off, free, no-squeeze or no-corners".to_string())
+            },
+            "--cover" => match args.next().map(|a| a.parse::<u64>()) {
+                Some(Ok(a)) => self.cover = Some(a),
+                _ => return Err("--cover needs a percentage".to_string())
+            },
+            "--units" => match args.next().map(|a| a.parse::<u64>()) {
+                Some(Ok(a)) => self.units = Some(a),
+                _ => return Err("--units needs a number of units".to_string())
+            },
+            "--health" => match args.next().map(|a| a.parse::<u64>()) {
+                Some(Ok(a)) => self.health = Some(a),
+                _ => return Err("--health needs a number".to_string())
+            },
+            "--initiative" => match args.next().map(|a| a.parse::<u64>()) {
+                Some(Ok(a)) => self.initiative = Some(a),
+                _ => return Err("--initiative needs a number".to_string())
+            },
+            "--accuracy" => match args.next().map(|a| a.parse::<u64>()) {
+                Some(Ok(a)) => self.accuracy = Some(a),
+                _ => return Err("--accuracy needs a percentage".to_string())
+            },
+            "--action-points" => match args.next().map(|a| a.parse::<u64>()) {
+                Some(Ok(a)) => self.action_points = Some(a),
+                _ => return Err("--action-points needs a number of points".to_string())
+            },
+            "--attacks" => match args.next() {
+                Some(a) => self.attacks = Some(
+                    a.split(',').map(|a| a.parse::<game::Attack>()).collect::<Result<_, _>>()?
+                ),
+                None => return Err("--attacks needs <name>:<damage>:<range>:<cost>, ...".to_string())
+            },
+            "--deploy" => match args.next() {
+                Some(a) => self.deploy = Some(a.parse::<game::DeployMode>()?),
+                None => return Err("--deploy needs lineup, alternate or hidden".to_string())
+            },
+            "--army" => {
+                let a = args.next().unwrap_or_default();
+                let (team, path) = match a.split_once(':') {
+                    Some(a) => a,
+                    None => return Err("--army needs <team>:<file>".to_string())
+                };
+                // a later army for the same team replaces the earlier one
+                let team = team.parse::<game::Team>()?;
+                self.army.retain(|(t, _)| *t != team);
+                self.army.push((team, path.to_string()));
+            },
+            "--build-army" => self.build_army = Some(true),
+            "--no-build-army" => self.build_army = Some(false),
+            "--budget" => match args.next().map(|a| a.parse::<u64>()) {
+                Some(Ok(a)) if a > 0 => self.budget = Some(a),
+                _ => return Err("--budget needs a number of points".to_string())
+            },
+            "--army-list" => match args.next() {
+                Some(a) => self.army_list = Some(a),
+                None => return Err("--army-list needs a file".to_string())
+            },
+            "--turn-limit" => match args.next().map(|a| a.parse::<u64>()) {
+                Some(Ok(a)) if a > 0 => self.turn_limit = Some(a),
+                _ => return Err("--turn-limit needs a number of rounds".to_string())
+            },
+            "--hold" => {
+                let a = args.next().unwrap_or_default();
+                let (tiles, rounds) = match a.split_once(':') {
+                    Some(a) => a,
+                    None => return Err("--hold needs <tiles>:<rounds>".to_string())
+                };
+                let rounds = match rounds.parse::<u64>() {
+                    Ok(r) if r > 0 => r,
+                    _ => return Err(format!("'{}' is not a number of rounds", rounds))
+                };
+                self.hold.push((parse_tiles(tiles)?, rounds));
+            },
+            "--vip" => match args.next() {
+                Some(a) => self.vip.push(a.parse::<game::Team>()?),
+                None => return Err("--vip needs a team".to_string())
+            },
+            "--extract" => {
+                let a = args.next().unwrap_or_default();
+                let (team, tiles) = match a.split_once(':') {
+                    Some(a) => a,
+                    None => return Err("--extract needs <team>:<tiles>".to_string())
+                };
+                self.extract.push((team.parse::<game::Team>()?, parse_tiles(tiles)?));
+            },
+            "--control" => match args.next() {
+                Some(a) => self.control.append(&mut parse_tiles(&a)?),
+                None => return Err("--control needs tiles".to_string())
+            },
+            "--control-radius" => match args.next().map(|a| a.parse::<f64>()) {
+                Some(Ok(a)) if a >= 0f64 => self.control_radius = Some(a),
+                _ => return Err("--control-radius needs a distance".to_string())
+            },
+            "--score-limit" => match args.next().map(|a| a.parse::<u64>()) {
+                Some(Ok(a)) if a > 0 => self.score_limit = Some(a),
+                _ => return Err("--score-limit needs a number of points".to_string())
+            },
+            "--campaign" => match args.next() {
+                Some(a) => self.campaign = Some(a),
+                None => return Err("--campaign needs a file".to_string())
+            },
+            "--save" => match args.next() {
+                Some(a) => self.save = Some(a),
+                None => return Err("--save needs a file".to_string())
+            },
+            "--skills" => match args.next() {
+                Some(a) => self.skills = Some(a),
+                None => return Err("--skills needs a file".to_string())
+            },
+            "--event-log" => match args.next() {
+                Some(a) => self.event_log = Some(a),
+                None => return Err("--event-log needs a file".to_string())
+            },
+            "--mission" => match args.next() {
+                Some(a) => self.mission = Some(a),
+                None => return Err("--mission needs a file".to_string())
+            },
+            "--export" => match args.next() {
+                Some(a) => {
+                    after_action::export_format(&a)?;
+                    self.export = Some(a);
+                },
+                None => return Err("--export needs a file".to_string())
+            },
+            "--config" => match args.next() {
+                Some(a) => self.config = Some(a),
+                None => return Err("--config needs a file".to_string())
+            },
+            "--print-config" => self.print_config = true,
+            _ => return Ok(false)
+        }
+        Ok(true)
+    }
+}
+
+/// parses the command line options
+pub fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut opts = Options::default();
+    while let Some(arg) = args.next() {
+        if !opts.parse_arg(&arg, &mut args)? {
+            return Err(format!("Unknown option '{}'", arg));
+        }
+    }
+    Ok(opts)
+}
+
+/// loads the skill tree from a file, or the built in one if there isn't one
+pub(crate) fn load_skills(path: &Option<String>) -> Result<game::SkillTree, String> {
+    let path = match path {
+        Some(a) => a,
+        None => return Ok(game::SkillTree::default_tree())
+    };
+
+    match std::fs::read_to_string(path) {
+        Ok(a) => game::SkillTree::parse(&a),
+        Err(e) => Err(format!("Failed to read skill tree '{}': {}", path, e))
+    }
+}
+
+/// loads the army list from a file, or the built in one if there isn't one
+fn load_army_list(path: &Option<String>) -> Result<game::ArmyList, String> {
+    let path = match path {
+        Some(a) => a,
+        None => return Ok(game::ArmyList::default_list())
+    };
+
+    match std::fs::read_to_string(path) {
+        Ok(a) => game::ArmyList::parse(&a).map_err(|e| format!("{} in army list '{}'", e, path)),
+        Err(e) => Err(format!("Failed to read army list '{}': {}", path, e))
+    }
+}
+
+/// the settings a match is set up with, once the command line has been
+/// layered over the config file and both over the defaults
+pub struct Settings {
+    /// the options the two layers add up to
+    pub options:    Options,
+    pub map:        game::MapSettings,
+    /// how many units each team's default squad has
+    pub squad_size: u64,
+    /// what every unit of a default squad starts with
+    pub stats:      game::UnitStats,
+    pub deploy:     game::DeployMode,
+    /// the army list armies are bought from
+    pub army_list:  game::ArmyList,
+    /// how many points each army can cost
+    pub budget:     u64,
+    pub echo:       bool,
+    pub build_army: bool,
+    pub undo_consent: bool,
+    pub fixed_initiative: bool
+}
+
+/// lays `top` over `base` field by field: whatever `top` sets wins, and a
+/// list `top` gives replaces the one in `base`. saved armies go by team
+fn layer(top: &Options, base: &Options) -> Options {
+    macro_rules! or {
+        ($field:ident) => {
+            top.$field.clone().or_else(|| base.$field.clone())
+        };
+    }
+    macro_rules! list {
+        ($field:ident) => {
+            if top.$field.is_empty() {
+                base.$field.clone()
+            } else {
+                top.$field.clone()
+            }
+        };
+    }
+
+    let mut army = top.army.clone();
+    army.extend(base.army.iter().filter(|(team, _)| !top.army.iter().any(|(t, _)| t == team)).cloned());
+
+    Options {
+        script: or!(script),
+        echo: or!(echo),
+        report: or!(report),
+        seed: or!(seed),
+        width: or!(width),
+        height: or!(height),
+        grid: or!(grid),
+        metric: or!(metric),
+        diagonals: or!(diagonals),
+        cover: or!(cover),
+        units: or!(units),
+        health: or!(health),
+        initiative: or!(initiative),
+        accuracy: or!(accuracy),
+        action_points: or!(action_points),
+        attacks: or!(attacks),
+        deploy: or!(deploy),
+        army,
+        build_army: or!(build_army),
+        budget: or!(budget),
+        army_list: or!(army_list),
+        undo_consent: or!(undo_consent),
+        fixed_initiative: or!(fixed_initiative),
+        turn_limit: or!(turn_limit),
+        hold: list!(hold),
+        vip: list!(vip),
+        extract: list!(extract),
+        control: list!(control),
+        control_radius: or!(control_radius),
+        score_limit: or!(score_limit),
+        campaign: or!(campaign),
+        save: or!(save),
+        skills: or!(skills),
+        event_log: or!(event_log),
+        mission: or!(mission),
+        export: or!(export),
+        // these only work on the command line
+        config: top.config.clone(),
+        print_config: top.print_config
+    }
+}
+
+impl Settings {
+    /// works out the settings `opts` ask for, taking anything they leave out
+    /// from `fallback` and then the defaults, and makes sure they go together
+    pub fn new(opts: &Options, fallback: Option<&Options>) -> Result<Self, String> {
+        let options = match fallback {
+            Some(base) => layer(opts, base),
+            None => opts.clone()
+        };
+
+        let map_defaults = game::MapSettings::default();
+        let map = game::MapSettings {
+            width: options.width.unwrap_or(map_defaults.width),
+            height: options.height.unwrap_or(map_defaults.height),
+            grid: options.grid.unwrap_or(map_defaults.grid),
+            metric: options.metric.unwrap_or(map_defaults.metric),
+            diagonals: options.diagonals.unwrap_or(map_defaults.diagonals),
+            cover: options.cover.unwrap_or(map_defaults.cover)
+        };
+        map.validate()?;
+
+        let stat_defaults = game::UnitStats::default();
+        let stats = game::UnitStats {
+            health: options.health.unwrap_or(stat_defaults.health),
+            initiative: options.initiative.unwrap_or(stat_defaults.initiative),
+            accuracy: options.accuracy.unwrap_or(stat_defaults.accuracy),
+            action_points: options.action_points.unwrap_or(stat_defaults.action_points),
+            attacks: options.attacks.clone().unwrap_or(stat_defaults.attacks)
+        };
+        stats.validate()?;
+
+        // each team has to fit in its own half of the map
+        let squad_size = options.units.unwrap_or(DEFAULT_SQUAD_SIZE);
+        let room = map.width * (map.height / 2);
+        if squad_size == 0 || squad_size > room {
+            return Err(format!(
+                "Squads need from 1 to {} units on a {} by {} map, not {}",
+                room,
+                map.width,
+                map.height,
+                squad_size
+            ));
+        }
+
+        let army_list = load_army_list(&options.army_list)?;
+        let budget = options.budget.unwrap_or(army_list.budget());
+
+        Ok(Settings {
+            map,
+            squad_size,
+            stats,
+            deploy: options.deploy.unwrap_or(game::DeployMode::Lineup),
+            army_list,
+            budget,
+            echo: options.echo.unwrap_or(false),
+            build_army: options.build_army.unwrap_or(false),
+            undo_consent: options.undo_consent.unwrap_or(false),
+            fixed_initiative: options.fixed_initiative.unwrap_or(false),
+            options
+        })
+    }
+
+    /// a team's units, numbered from `first_id`: its saved army if it has
+    /// one, the default squad otherwise
+    pub(crate) fn army(&self, team: game::Team, first_id: u64) -> Result<Vec<game::Unit>, String> {
+        let path = match self.options.army.iter().find(|(t, _)| *t == team) {
+            Some((_, path)) => path,
+            None => return Ok(default_squad(team, first_id, self.squad_size, &self.stats))
+        };
+        let army = builder::load_army(path)?;
+        army.validate(&self.army_list, self.budget).map_err(|e| format!("{}'s army won't do: {}", team, e))?;
+        army.units(&self.army_list, team, first_id, &self.stats)
+    }
+
+    /// writes the settings out as a config file, with everything a config
+    /// file can set that's in effect
+    pub fn to_config(&self) -> String {
+        let opts = &self.options;
+        let tiles = |tiles: &[game::Position]| -> String {
+            tiles.iter().map(|t| t.to_string()).collect::<Vec<_>>().join(",")
+        };
+        let attacks: Vec<String> = self.stats.attacks.iter().map(|a| a.to_string()).collect();
+
+        let mut lines = vec!["# the settings in effect, which work as a config file too".to_string()];
+        let mut push = |key: &str, value: Option<String>| {
+            if let Some(value) = value {
+                lines.push(format!("{} {}", key, value));
+            }
+        };
+        push("script", opts.script.clone());
+        push("report", opts.report.clone());
+        push("seed", opts.seed.map(|s| s.to_string()));
+        push("width", Some(self.map.width.to_string()));
+        push("height", Some(self.map.height.to_string()));
+        push("grid", Some(self.map.grid.to_string()));
+        push("metric", Some(self.map.metric.to_string()));
+        push("diagonals", Some(self.map.diagonals.to_string()));
+        push("cover", Some(self.map.cover.to_string()));
+        push("units", Some(self.squad_size.to_string()));
+        push("health", Some(self.stats.health.to_string()));
+        push("initiative", Some(self.stats.initiative.to_string()));
+        push("accuracy", Some(self.stats.accuracy.to_string()));
+        push("action-points", Some(self.stats.action_points.to_string()));
+        push("attacks", Some(attacks.join(", ")));
+        push("deploy", Some(self.deploy.to_string()));
+        for (team, path) in &opts.army {
+            push("army", Some(format!("{}:{}", team, path)));
+        }
+        push("army-list", opts.army_list.clone());
+        push("budget", Some(self.budget.to_string()));
+        push("turn-limit", opts.turn_limit.map(|t| t.to_string()));
+        for (hold, rounds) in &opts.hold {
+            push("hold", Some(format!("{}:{}", tiles(hold), rounds)));
+        }
+        for team in &opts.vip {
+            push("vip", Some(team.to_string()));
+        }
+        for (team, zone) in &opts.extract {
+            push("extract", Some(format!("{}:{}", team, tiles(zone))));
+        }
+        if !opts.control.is_empty() {
+            push("control", Some(tiles(&opts.control)));
+        }
+        push("control-radius", opts.control_radius.map(|r| r.to_string()));
+        push("score-limit", opts.score_limit.map(|s| s.to_string()));
+        push("campaign", opts.campaign.clone());
+        push("save", opts.save.clone());
+        push("skills", opts.skills.clone());
+        push("event-log", opts.event_log.clone());
+        push("mission", opts.mission.clone());
+        push("export", opts.export.clone());
+
+        // switches are only written when they're on
+        let switches = [
+            ("echo", self.echo),
+            ("build-army", self.build_army),
+            ("undo-consent", self.undo_consent),
+            ("fixed-initiative", self.fixed_initiative)
+        ];
+        for (switch, on) in switches {
+            if on {
+                lines.push(switch.to_string());
+            }
+        }
+        lines.join("\n") + "\n"
+    }
+}
+
+/// reads a config file, where each line is one of the command line options
+/// without its dashes, e.g. `width 24` or `undo-consent`
+pub fn load(path: &str) -> Result<Options, String> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(a) => a,
+        Err(e) => return Err(format!("Failed to read config '{}': {}", path, e))
+    };
+
+    let mut args = Vec::new();
+    for line in contents.lines().map(|l| l.trim()) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (key, value) = match line.split_once(char::is_whitespace) {
+            Some((k, v)) => (k, Some(v.trim())),
+            None => (line, None)
+        };
+        if key == "config" || key == "print-config" {
+            return Err(format!("'{}' only works on the command line, not in config '{}'", key, path));
+        }
+        args.push(format!("--{}", key));
+        args.extend(value.map(|v| v.to_string()));
+    }
+
+    parse_args(args.into_iter()).map_err(|e| format!("{} in config '{}'", e, path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Options {
+        parse_args(line.split_whitespace().map(|a| a.to_string())).ok().unwrap()
+    }
+
+    /// writes `contents` to a config file of its own and loads it
+    fn load_str(name: &str, contents: &str) -> Result<Options, String> {
+        let path = std::env::temp_dir().join(format!("crap_tactica_{}_{}.cfg", name, std::process::id()));
+        std::fs::write(&path, contents).unwrap();
+        let loaded = load(path.to_str().unwrap());
+        let _ = std::fs::remove_file(&path);
+        loaded
+    }
+
+    #[test]
+    fn unknown_options_are_left_to_the_caller() {
+        let mut opts = Options::default();
+        let mut rest = vec!["24".to_string()].into_iter();
+        assert_eq!(opts.parse_arg("--matches", &mut rest), Ok(false));
+        assert_eq!(opts.parse_arg("--width", &mut rest), Ok(true));
+        assert_eq!(opts.width, Some(24));
+        assert!(parse_args(vec!["--matches".to_string()].into_iter()).is_err());
+    }
+
+    #[test]
+    fn the_top_layer_wins_field_by_field() {
+        let base = args("--width 20 --height 12 --undo-consent --vip r --army r:red.army --army b:blue.army");
+        let top = args("--width 8 --no-undo-consent --army b:other.army");
+        let settings = Settings::new(&top, Some(&base)).ok().unwrap();
+        assert_eq!((settings.map.width, settings.map.height), (8, 12));
+        assert!(!settings.undo_consent);
+        assert!(settings.options.vip == vec![game::Team::Redfor]);
+
+        let army: Vec<String> = settings.options.army.iter().map(|(t, p)| format!("{}:{}", t, p)).collect();
+        assert_eq!(army, vec!["Bluefor:other.army", "Redfor:red.army"]);
+    }
+
+    #[test]
+    fn settings_fall_back_to_the_defaults() {
+        let settings = Settings::new(&Options::default(), None).ok().unwrap();
+        assert_eq!(settings.squad_size, DEFAULT_SQUAD_SIZE);
+        assert_eq!(settings.stats.health, game::UnitStats::default().health);
+        assert!(settings.deploy == game::DeployMode::Lineup);
+        assert!(!settings.build_army && !settings.echo && !settings.fixed_initiative);
+    }
+
+    #[test]
+    fn squads_have_to_fit_their_half_of_the_map() {
+        assert!(Settings::new(&args("--width 4 --height 4 --units 8"), None).is_ok());
+        assert!(Settings::new(&args("--width 4 --height 4 --units 9"), None).is_err());
+        assert!(Settings::new(&args("--units 0"), None).is_err());
+        assert!(Settings::new(&args("--action-points 1"), None).is_err());
+    }
+
+    #[test]
+    fn printed_settings_load_back_the_same() {
+        let opts = args("--seed 7 --grid hex --units 2 --health 150 --attacks Poke:10:2:1 --hold 7x7,8x8:2 --fixed-initiative");
+        let settings = Settings::new(&opts, None).ok().unwrap();
+        let config = settings.to_config();
+
+        let loaded = load_str("round_trip", &config).ok().unwrap();
+        let reloaded = Settings::new(&loaded, None).ok().unwrap();
+        assert_eq!(reloaded.to_config(), config);
+        assert_eq!(reloaded.stats.attacks.len(), 1);
+        assert!(reloaded.fixed_initiative);
+    }
+
+    #[test]
+    fn config_files_skip_comments_and_refuse_command_line_only_options() {
+        let loaded = load_str("comments", "# a comment\n\nwidth 24\n  echo\n").ok().unwrap();
+        assert_eq!(loaded.width, Some(24));
+        assert_eq!(loaded.echo, Some(true));
+
+        let err = load_str("nested", "config other.cfg\n").err().unwrap();
+        assert!(err.starts_with("'config' only works on the command line"));
+        let err = load_str("unknown", "colour blue\n").err().unwrap();
+        assert!(err.starts_with("Unknown option '--colour' in config"));
+    }
+}
@@ -0,0 +1,75 @@
+// prints what happens in the game to the terminal
+
+use crate::game;
+
+/// a subscriber that prints the game's events as they happen
+pub struct Console;
+
+impl game::Subscriber for Console {
+    fn notify(&mut self, event: &game::Event) {
+        match event {
+            // the prompt already says whose turn it is
+            game::Event::TurnStarted { .. } => (),
+            game::Event::UnitMoved { name, from, to, .. } => {
+                println!("{} moved from {} to {}", name, from, to);
+            },
+            game::Event::AttackResolved { attacker, target, attack, result, reaction, .. } => {
+                if *reaction {
+                    println!("[!] {} fires {} from overwatch!", attacker, attack);
+                }
+                match result {
+                    game::AttackResult::Hit { health } =>
+                        println!("[+] Attack hit! {} is now at {} hp!", target, health),
+                    // the death gets its own event
+                    game::AttackResult::Killed => (),
+                    game::AttackResult::Missed => println!("[-] Attack missed!")
+                }
+            },
+            game::Event::UnitDied { name, .. } => println!("[+] {} was killed!", name),
+            game::Event::ItemUsed { name, item, tile, .. } => match item.effect() {
+                game::Effect::Smoke { rounds, .. } =>
+                    println!("[+] {} covers {} in smoke for {} round(s)", name, tile, rounds),
+                _ => println!("[+] {} uses the {} on {}", name, item.name(), tile)
+            },
+            game::Event::ItemPickedUp { name, item, tile, .. } => {
+                println!("[+] {} picks up the {} lying on {}", name, item.name(), tile);
+            },
+            game::Event::UnitHealed { name, health, max_health, .. } => {
+                println!("[+] {} is healed up to {}/{} hp", name, health, max_health);
+            },
+            game::Event::UnitRevived { name, health, .. } => {
+                println!("[+] {} is back on their feet with {} hp!", name, health);
+            },
+            game::Event::BuffApplied { name, kind, amount, last_round, .. } => match last_round {
+                Some(round) => println!("[+] {} gets +{} {} until the end of round {}", name, amount, kind, round),
+                None => println!("[+] {} gets +{} {} on the next attack", name, amount, kind)
+            },
+            game::Event::LevelUp { name, level, bonuses, can_promote, .. } => {
+                let bonuses: Vec<String> = bonuses.iter()
+                    .map(|(stat, amount)| format!("+{} {}", amount, stat))
+                    .collect();
+                if bonuses.is_empty() {
+                    println!("[+] {} reached level {}!", name, level);
+                } else {
+                    println!("[+] {} reached level {}! ({})", name, level, bonuses.join(", "));
+                }
+                if *can_promote {
+                    println!("[+] {} can be promoted, see 'promote'", name);
+                }
+            },
+            // the top of the next round is announced instead
+            game::Event::RoundEnded { .. } => (),
+            // the undo and redo commands say what happened
+            game::Event::Committed | game::Event::Amended | game::Event::Undone | game::Event::Redone => (),
+            game::Event::UnitSpawned { name, team, position, .. } => {
+                println!("[!] {} ({}) arrives at {}", name, team, position);
+            },
+            game::Event::Message { text } => {
+                for line in text.lines() {
+                    println!("[!] {}", line);
+                }
+            },
+            game::Event::GameOver { outcome } => println!("[+] {}", outcome)
+        }
+    }
+}
@@ -0,0 +1,28 @@
+// writes every event the game emits to a file
+
+use std::io::Write;
+
+use crate::game;
+
+/// a subscriber that writes each event as a line of text, for replaying or
+/// picking apart a match afterwards
+pub struct EventLog {
+    file: std::fs::File
+}
+
+impl EventLog {
+    /// creates the log file, replacing any that is already there
+    pub fn create(path: &str) -> Result<Self, String> {
+        match std::fs::File::create(path) {
+            Ok(file) => Ok(EventLog { file }),
+            Err(e) => Err(format!("Failed to create event log '{}': {}", path, e))
+        }
+    }
+}
+
+impl game::Subscriber for EventLog {
+    fn notify(&mut self, event: &game::Event) {
+        // a full disk shouldn't stop the match
+        let _ = writeln!(self.file, "{}", event);
+    }
+}
@@ -0,0 +1,139 @@
+// defines the support abilities units use on their friends
+
+/// a stat a buff raises
+#[derive(Clone, Copy, PartialEq)]
+pub enum BuffKind {
+    Damage,
+    Accuracy
+}
+
+/// display format implementation
+impl std::fmt::Display for BuffKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let printable = match self {
+            BuffKind::Damage => "dmg",
+            BuffKind::Accuracy => "accuracy",
+        };
+
+        write!(f, "{}", printable)
+    }
+}
+
+/// a temporary boost to a unit's stats
+#[derive(Clone, Copy)]
+pub struct Buff {
+    pub kind:       BuffKind,
+    pub amount:     u64,
+    /// the last round the buff is active for
+    pub last_round: u64
+}
+
+/// what happens when an ability is used
+#[derive(Clone, Copy, PartialEq)]
+pub enum AbilityEffect {
+    /// restores hp, up to the unit's max health
+    Heal(u64),
+    /// raises a stat for a number of rounds, counting the current one
+    Buff {
+        kind:   BuffKind,
+        amount: u64,
+        rounds: u64
+    },
+    /// brings a downed unit back with some hp
+    Revive(u64)
+}
+
+/// an ability a unit can use on itself or its friends
+#[derive(Clone)]
+pub struct Ability {
+    name:   String,
+    effect: AbilityEffect,
+    range:  f64,
+    cost:   u64,
+    uses:   u64
+}
+
+impl Ability {
+    /// creates a new ability that can be used `uses` times a battle
+    pub fn new(name: String, effect: AbilityEffect, range: f64, cost: u64, uses: u64) -> Self {
+        Ability {
+            name,
+            effect,
+            range,
+            cost,
+            uses
+        }
+    }
+
+    /// returns the abilities every unit goes into battle with
+    pub fn default_set() -> Vec<Self> {
+        vec![
+            Ability::new("First Aid".to_string(), AbilityEffect::Heal(30), 1f64, 2, 2),
+            Ability::new(
+                "Spot".to_string(),
+                AbilityEffect::Buff { kind: BuffKind::Accuracy, amount: 15, rounds: 2 },
+                3f64,
+                1,
+                2
+            ),
+            Ability::new(
+                "Rally".to_string(),
+                AbilityEffect::Buff { kind: BuffKind::Damage, amount: 10, rounds: 2 },
+                3f64,
+                2,
+                1
+            ),
+            Ability::new("Revive".to_string(), AbilityEffect::Revive(30), 1f64, 4, 1)
+        ]
+    }
+
+    /// returns the name of the ability
+    pub fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    /// returns what the ability does
+    pub fn effect(&self) -> AbilityEffect {
+        self.effect
+    }
+
+    /// returns how far away the ability reaches
+    pub fn range(&self) -> f64 {
+        self.range
+    }
+
+    /// returns the action points it takes to use the ability
+    pub fn cost(&self) -> u64 {
+        self.cost
+    }
+
+    /// returns how many more times the ability can be used this battle
+    pub fn uses(&self) -> u64 {
+        self.uses
+    }
+
+    /// uses up one of the ability's uses
+    pub fn spend_use(&mut self) {
+        self.uses = self.uses.saturating_sub(1);
+    }
+}
+
+/// display format implementation
+impl std::fmt::Display for Ability {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let effect = match self.effect {
+            AbilityEffect::Heal(hp) => format!("heals {} hp", hp),
+            AbilityEffect::Buff { kind, amount, rounds } => format!("+{} {} for {} rounds", amount, kind, rounds),
+            AbilityEffect::Revive(hp) => format!("revives a downed ally with {} hp", hp)
+        };
+        write!(
+            f,
+            "{} ({}, range {}, {} AP, {} left)",
+            self.name,
+            effect,
+            self.range,
+            self.cost,
+            self.uses
+        )
+    }
+}
@@ -0,0 +1,316 @@
+// defines the unit archetypes and loadouts armies are bought from, and the armies themselves
+
+use super::{
+    Attack,
+    Position,
+    Team,
+    Unit,
+    UnitStats
+};
+
+/// the army list used when none is loaded, in the same format as an army list file
+///
+/// three riflemen with the standard loadout are the default squad, and fit
+/// the default budget exactly
+pub const DEFAULT_ARMY_LIST: &str = "
+# budget <points>
+budget 75
+
+# archetype <name> <points> <health> <initiative> <most per army>
+archetype Rifleman 20 100 10 4
+archetype Scout 15 70 14 2
+archetype Heavy 35 160 8 1
+
+# loadout <name> <points>: <attack>:<damage>:<range>:<cost>, ...
+loadout Standard 5: Fight:50:1:2, Shoot:30:10:3
+loadout Brawler 5: Fight:60:1:2, Haymaker:70:1:3
+loadout Marksman 10: Fight:40:1:2, Aimed Shot:45:12:4
+loadout Support 0: Fight:40:1:2, Pistol:20:6:2
+";
+
+/// a kind of unit an army can buy
+#[derive(Clone)]
+pub struct Archetype {
+    pub name:       String,
+    pub points:     u64,
+    pub health:     u64,
+    pub initiative: u64,
+    /// how many of them a single army can have
+    pub limit:      u64
+}
+
+/// a set of attacks an army can buy for any of its units
+#[derive(Clone)]
+pub struct Loadout {
+    pub name:       String,
+    pub points:     u64,
+    pub attacks:    Vec<Attack>
+}
+
+/// display format implementation
+impl std::fmt::Display for Archetype {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{} - {} pts, {} hp, initiative {}, up to {} per army",
+            self.name,
+            self.points,
+            self.health,
+            self.initiative,
+            self.limit
+        )
+    }
+}
+
+/// display format implementation
+impl std::fmt::Display for Loadout {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let attacks: Vec<String> = self.attacks.iter()
+            .map(|a| format!("{} ({} dmg, range {}, {} AP)", a.name(), a.damage(), a.range(), a.cost()))
+            .collect();
+        write!(f, "{} - {} pts: {}", self.name, self.points, attacks.join(", "))
+    }
+}
+
+/// the archetypes and loadouts armies are bought from, and the points each
+/// side gets to spend
+#[derive(Clone)]
+pub struct ArmyList {
+    budget:     u64,
+    archetypes: Vec<Archetype>,
+    loadouts:   Vec<Loadout>
+}
+
+impl ArmyList {
+    /// returns the built in army list
+    pub fn default_list() -> Self {
+        Self::parse(DEFAULT_ARMY_LIST).expect("the default army list should parse")
+    }
+
+    /// parses an army list, see `DEFAULT_ARMY_LIST` for the format
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut list = ArmyList {
+            budget: 0,
+            archetypes: Vec::new(),
+            loadouts: Vec::new()
+        };
+
+        for line in text.lines().map(|l| l.trim()) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let bad = || format!("Bad army list line '{}'", line);
+            match words[0] {
+                "budget" if words.len() == 2 => {
+                    list.budget = words[1].parse::<u64>().map_err(|_| bad())?;
+                },
+                "archetype" if words.len() == 6 => {
+                    let stats: Result<Vec<u64>, _> = words[2..].iter().map(|w| w.parse::<u64>()).collect();
+                    match stats.as_deref() {
+                        Ok([points, health, initiative, limit]) if *health > 0 => list.archetypes.push(Archetype {
+                            name: words[1].to_string(),
+                            points: *points,
+                            health: *health,
+                            initiative: *initiative,
+                            limit: *limit
+                        }),
+                        _ => return Err(bad())
+                    }
+                },
+                "loadout" if words.len() >= 4 => {
+                    list.loadouts.push(Self::parse_loadout(line)?);
+                },
+                _ => return Err(bad())
+            }
+        }
+
+        if list.budget == 0 {
+            return Err("The army list needs a budget".to_string());
+        }
+        if list.archetypes.is_empty() || list.loadouts.is_empty() {
+            return Err("The army list needs at least one archetype and one loadout".to_string());
+        }
+        Ok(list)
+    }
+
+    /// parses a `loadout <name> <points>: <attack>, ...` line
+    fn parse_loadout(line: &str) -> Result<Loadout, String> {
+        let bad = || format!("Bad army list line '{}'", line);
+
+        let rest = line.trim_start_matches("loadout").trim();
+        let (head, attacks) = rest.split_once(':').ok_or_else(bad)?;
+        let (name, points) = head.trim().split_once(' ').ok_or_else(bad)?;
+        let points = points.trim().parse::<u64>().map_err(|_| bad())?;
+
+        // each attack is <name>:<damage>:<range>:<cost>
+        let attacks: Result<Vec<Attack>, String> = attacks.split(',').map(|a| a.parse::<Attack>()).collect();
+        Ok(Loadout {
+            name: name.to_string(),
+            points,
+            attacks: attacks.map_err(|_| bad())?
+        })
+    }
+
+    /// returns how many points each side gets unless the match says otherwise
+    pub fn budget(&self) -> u64 {
+        self.budget
+    }
+
+    /// returns the archetypes units can be bought as
+    pub fn archetypes(&self) -> &[Archetype] {
+        &self.archetypes
+    }
+
+    /// returns the loadouts units can be bought with
+    pub fn loadouts(&self) -> &[Loadout] {
+        &self.loadouts
+    }
+
+    /// returns the archetype called `name`, ignoring case
+    pub fn archetype(&self, name: &str) -> Option<&Archetype> {
+        self.archetypes.iter().find(|a| a.name.eq_ignore_ascii_case(name))
+    }
+
+    /// returns the loadout called `name`, ignoring case
+    pub fn loadout(&self, name: &str) -> Option<&Loadout> {
+        self.loadouts.iter().find(|l| l.name.eq_ignore_ascii_case(name))
+    }
+}
+
+/// a unit bought for an army
+#[derive(Clone)]
+pub struct Pick {
+    pub name:       String,
+    pub archetype:  String,
+    pub loadout:    String
+}
+
+/// the units a side has bought, which can be saved and used again
+#[derive(Clone)]
+pub struct Army {
+    picks:  Vec<Pick>
+}
+
+impl Army {
+    /// creates an army with nobody in it
+    pub fn new() -> Self {
+        Army {
+            picks: Vec::new()
+        }
+    }
+
+    /// returns the units bought so far
+    pub fn picks(&self) -> &[Pick] {
+        &self.picks
+    }
+
+    /// buys a unit
+    pub fn add(&mut self, pick: Pick) {
+        self.picks.push(pick);
+    }
+
+    /// takes back the unit at `idx`
+    pub fn remove(&mut self, idx: usize) -> Pick {
+        self.picks.remove(idx)
+    }
+
+    /// returns what the army costs, failing if it has anything the list doesn't
+    pub fn cost(&self, list: &ArmyList) -> Result<u64, String> {
+        let mut total = 0;
+        for pick in &self.picks {
+            let archetype = match list.archetype(&pick.archetype) {
+                Some(a) => a,
+                None => return Err(format!("There is no archetype called '{}'", pick.archetype))
+            };
+            let loadout = match list.loadout(&pick.loadout) {
+                Some(a) => a,
+                None => return Err(format!("There is no loadout called '{}'", pick.loadout))
+            };
+            total += archetype.points + loadout.points;
+        }
+        Ok(total)
+    }
+
+    /// makes sure the army can be fielded in a match with `budget` points,
+    /// keeping to the list's limit on each archetype
+    pub fn validate(&self, list: &ArmyList, budget: u64) -> Result<(), String> {
+        if self.picks.is_empty() {
+            return Err("The army has no units".to_string());
+        }
+        let cost = self.cost(list)?;
+        for archetype in list.archetypes() {
+            let count = self.picks.iter()
+                .filter(|p| p.archetype.eq_ignore_ascii_case(&archetype.name))
+                .count() as u64;
+            if count > archetype.limit {
+                return Err(format!(
+                    "An army can have at most {} {}, not {}",
+                    archetype.limit,
+                    archetype.name,
+                    count
+                ));
+            }
+        }
+        if cost > budget {
+            return Err(format!("The army costs {} points, over the budget of {}", cost, budget));
+        }
+        Ok(())
+    }
+
+    /// returns the army's units for a battle, numbered from `first_id`, with
+    /// their archetype and loadout on top of the `base` stats
+    pub fn units(&self, list: &ArmyList, team: Team, first_id: u64, base: &UnitStats) -> Result<Vec<Unit>, String> {
+        let mut units = Vec::new();
+        for (idx, pick) in self.picks.iter().enumerate() {
+            let (archetype, loadout) = match (list.archetype(&pick.archetype), list.loadout(&pick.loadout)) {
+                (Some(a), Some(l)) => (a, l),
+                _ => return Err(format!("{} isn't on the army list", pick.name))
+            };
+            let stats = UnitStats {
+                health: archetype.health,
+                initiative: archetype.initiative,
+                attacks: loadout.attacks.clone(),
+                ..base.clone()
+            };
+            units.push(Unit::from_stats(
+                pick.name.clone(),
+                first_id + idx as u64,
+                team,
+                &stats,
+                Position::new(0, 0)
+            ));
+        }
+        Ok(units)
+    }
+
+    /// writes the army as text, one `unit <archetype> <loadout> <name>` line per unit
+    pub fn to_text(&self) -> String {
+        let mut text = String::from("# unit <archetype> <loadout> <name>\n");
+        for pick in &self.picks {
+            text.push_str(&format!("unit {} {} {}\n", pick.archetype, pick.loadout, pick.name));
+        }
+        text
+    }
+
+    /// reads an army back from the text written by `to_text`
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut army = Army::new();
+        for line in text.lines().map(|l| l.trim()) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let words: Vec<&str> = line.splitn(4, ' ').collect();
+            match words.as_slice() {
+                ["unit", archetype, loadout, name] if !name.trim().is_empty() => army.add(Pick {
+                    name: name.trim().to_string(),
+                    archetype: archetype.to_string(),
+                    loadout: loadout.to_string()
+                }),
+                _ => return Err(format!("Bad army line '{}'", line))
+            }
+        }
+        Ok(army)
+    }
+}
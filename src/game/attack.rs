@@ -4,6 +4,7 @@ pub struct Attack {
     name: String,
     damage: u64,
     range: f64,
+    cost: u64,
     // TODO: Can add effects creators down here at some point
 }
 
@@ -13,12 +14,14 @@ impl Attack {
     pub fn new(
         name: String,
         damage: u64,
-        range: f64
+        range: f64,
+        cost: u64
     ) -> Self {
         Attack {
             name, 
             damage,
-            range
+            range,
+            cost
         }
     }
 
@@ -32,8 +35,68 @@ impl Attack {
         self.damage
     }
 
+    /// returns the action point cost of the attack
+    pub fn cost(&self) -> u64 {
+        self.cost
+    }
+
     /// returns the name of the attack
     pub fn name(&self) -> String{
         self.name.clone()
     }
-}
\ No newline at end of file
+}
+
+/// display format implementation, as `<name>:<damage>:<range>:<cost>`
+impl std::fmt::Display for Attack {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}:{}:{}:{}", self.name, self.damage, self.range, self.cost)
+    }
+}
+
+/// parses an attack written as `<name>:<damage>:<range>:<cost>`
+impl std::str::FromStr for Attack {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = s.split(':').collect();
+        if fields.len() != 4 {
+            return Err(format!("'{}' is not an attack, expected <name>:<damage>:<range>:<cost>", s.trim()));
+        }
+        match (fields[1].trim().parse::<u64>(), fields[2].trim().parse::<f64>(), fields[3].trim().parse::<u64>()) {
+            // NaN would never be in or out of range
+            (Ok(_), Ok(range), Ok(_)) if !range.is_finite() || range < 0f64 =>
+                Err(format!("'{}' needs a range of 0 or more, not {}", s.trim(), fields[2].trim())),
+            (Ok(damage), Ok(range), Ok(cost)) if !fields[0].trim().is_empty() =>
+                Ok(Attack::new(fields[0].trim().to_string(), damage, range, cost)),
+            _ => Err(format!("'{}' is not an attack, expected <name>:<damage>:<range>:<cost>", s.trim()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_what_it_writes() {
+        let attack: Attack = "Aimed Shot:45:12.5:4".parse().unwrap();
+        assert_eq!(attack.name(), "Aimed Shot");
+        assert_eq!(attack.cost(), 4);
+        assert_eq!(attack.to_string(), "Aimed Shot:45:12.5:4");
+        assert_eq!(attack.to_string().parse::<Attack>().unwrap().to_string(), attack.to_string());
+    }
+
+    #[test]
+    fn rejects_missing_or_bad_fields() {
+        for bad in ["Shoot:30:10", ":30:10:3", "Shoot:lots:10:3", "Shoot:30:10:3:1"] {
+            assert!(bad.parse::<Attack>().is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn rejects_ranges_that_arent_a_distance() {
+        for bad in ["Shoot:30:NaN:3", "Shoot:30:inf:3", "Shoot:30:-1:3"] {
+            assert!(bad.parse::<Attack>().is_err(), "{}", bad);
+        }
+    }
+}
@@ -0,0 +1,447 @@
+// defines the battlefield, a grid of tiles that each keep their own layers
+
+use super::{
+    Diagonals,
+    Item,
+    Metric,
+    Movement,
+    Position,
+    Topology
+};
+
+/// how many tiles wide and high a map is unless told otherwise
+pub const DEFAULT_MAP_SIZE: u64 = 16;
+/// the shortest a side of the map can be
+pub const MIN_MAP_SIZE: u64 = 4;
+/// the longest a side of the map can be
+pub const MAX_MAP_SIZE: u64 = 1000;
+/// the percentage of tiles that get cover unless told otherwise
+pub const DEFAULT_COVER: u64 = 20;
+
+/// what the map of a match looks like and the rules for getting around it
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct MapSettings {
+    /// how many columns across the map is
+    pub width:      u64,
+    /// how many rows up the map is
+    pub height:     u64,
+    pub grid:       Topology,
+    /// how ranges are measured, on square maps
+    pub metric:     Metric,
+    /// which diagonal moves are allowed, on square maps
+    pub diagonals:  Diagonals,
+    /// the percentage of tiles between the two edges that get cover
+    pub cover:      u64
+}
+
+impl Default for MapSettings {
+    fn default() -> Self {
+        MapSettings {
+            width: DEFAULT_MAP_SIZE,
+            height: DEFAULT_MAP_SIZE,
+            grid: Topology::Square,
+            metric: Metric::Euclidean,
+            diagonals: Diagonals::Off,
+            cover: DEFAULT_COVER
+        }
+    }
+}
+
+impl MapSettings {
+    /// makes sure the settings go together
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, tiles) in [("width", self.width), ("height", self.height)] {
+            if !(MIN_MAP_SIZE..=MAX_MAP_SIZE).contains(&tiles) {
+                return Err(format!(
+                    "The map's {} needs to be from {} to {} tiles, not {}",
+                    name,
+                    MIN_MAP_SIZE,
+                    MAX_MAP_SIZE,
+                    tiles
+                ));
+            }
+        }
+        if self.cover > 100 {
+            return Err(format!("Cover is a percentage of the map, not {}", self.cover));
+        }
+        if self.grid == Topology::Hex && self.metric != Metric::Euclidean {
+            return Err("Hex maps always measure range in hexes, the metric is for square maps".to_string());
+        }
+        if self.grid == Topology::Hex && self.diagonals != Diagonals::Off {
+            return Err("Hex maps don't have diagonals, they're for square maps".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// what the ground of a tile is made of
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Terrain {
+    /// open ground anyone can cross
+    Open,
+    /// cover that blocks movement and sight until a blast knocks it down
+    Wall
+}
+
+impl Terrain {
+    /// returns if units can stand on it
+    pub fn passable(&self) -> bool {
+        match self {
+            Terrain::Open => true,
+            Terrain::Wall => false
+        }
+    }
+
+    /// returns if it blocks line of sight
+    pub fn blocks_sight(&self) -> bool {
+        match self {
+            Terrain::Open => false,
+            Terrain::Wall => true
+        }
+    }
+}
+
+/// something lingering on a tile
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum TileEffect {
+    /// smoke that lasts until the end of `last_round`
+    Smoke {
+        last_round: u64
+    }
+}
+
+/// a single square of the board
+#[derive(Clone)]
+pub struct Tile {
+    terrain:    Terrain,
+    occupant:   Option<u64>,
+    items:      Vec<Item>,
+    effects:    Vec<TileEffect>
+}
+
+impl Tile {
+    /// creates an empty tile of open ground
+    fn new() -> Self {
+        Tile {
+            terrain: Terrain::Open,
+            occupant: None,
+            items: Vec::new(),
+            effects: Vec::new()
+        }
+    }
+
+    /// returns the ground of the tile
+    pub fn terrain(&self) -> Terrain {
+        self.terrain
+    }
+
+    /// changes the ground of the tile
+    pub fn set_terrain(&mut self, terrain: Terrain) {
+        self.terrain = terrain;
+    }
+
+    /// returns the id of the unit standing on the tile
+    pub fn occupant(&self) -> Option<u64> {
+        self.occupant
+    }
+
+    /// puts a unit on the tile, or clears it with `None`
+    pub fn set_occupant(&mut self, occupant: Option<u64>) {
+        self.occupant = occupant;
+    }
+
+    /// returns the items lying on the tile
+    pub fn items(&self) -> &[Item] {
+        &self.items
+    }
+
+    /// drops an item on the tile
+    pub fn add_item(&mut self, item: Item) {
+        self.items.push(item);
+    }
+
+    /// picks up everything lying on the tile
+    pub fn take_items(&mut self) -> Vec<Item> {
+        std::mem::take(&mut self.items)
+    }
+
+    /// returns what is lingering on the tile
+    pub fn effects(&self) -> &[TileEffect] {
+        &self.effects
+    }
+
+    /// leaves something lingering on the tile
+    pub fn add_effect(&mut self, effect: TileEffect) {
+        self.effects.push(effect);
+    }
+
+    /// returns if nothing is stopping a unit from stepping onto the tile
+    pub fn is_open(&self) -> bool {
+        self.terrain.passable() && self.occupant.is_none()
+    }
+
+    /// copies the terrain, items and effects of `other`, leaving whoever
+    /// is standing here alone
+    pub fn restore(&mut self, other: &Tile) {
+        self.terrain = other.terrain;
+        self.items = other.items.clone();
+        self.effects = other.effects.clone();
+    }
+}
+
+/// the battlefield
+///
+/// `x` counts rows up the screen and `y` counts columns across it
+#[derive(Clone)]
+pub struct Board {
+    rows:       usize,
+    cols:       usize,
+    topology:   Topology,
+    metric:     Metric,
+    diagonals:  Diagonals,
+    tiles:      Vec<Vec<Tile>>
+}
+
+impl Board {
+    /// creates a board of open ground laid out by `map`
+    pub fn new(map: &MapSettings) -> Self {
+        let (rows, cols) = (map.height as usize, map.width as usize);
+        Board {
+            rows,
+            cols,
+            topology: map.grid,
+            metric: map.metric,
+            diagonals: map.diagonals,
+            tiles: vec![vec![Tile::new(); cols]; rows]
+        }
+    }
+
+    /// returns how many rows the board has
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// returns how many columns the board has
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// returns how the tiles of the board fit together
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    /// returns how ranges are measured on the board
+    pub fn metric(&self) -> Metric {
+        self.metric
+    }
+
+    /// returns every direction a unit can move in
+    pub fn directions(&self) -> &'static [Movement] {
+        self.topology.directions(self.diagonals)
+    }
+
+    /// returns if a step in direction `mov` cuts across a square diagonally
+    pub fn is_diagonal(&self, mov: Movement) -> bool {
+        self.topology == Topology::Square && mov.is_diagonal()
+    }
+
+    /// returns if a tile is on the board
+    pub fn contains(&self, pos: Position) -> bool {
+        pos.x() < self.rows && pos.y() < self.cols
+    }
+
+    /// returns the tile at `pos`, if it's on the board
+    pub fn tile(&self, pos: Position) -> Option<&Tile> {
+        if !self.contains(pos) {
+            return None;
+        }
+        self.tiles.get(pos.x())?.get(pos.y())
+    }
+
+    /// returns the tile at `pos` to change it, if it's on the board
+    pub fn tile_mut(&mut self, pos: Position) -> Option<&mut Tile> {
+        if !self.contains(pos) {
+            return None;
+        }
+        self.tiles.get_mut(pos.x())?.get_mut(pos.y())
+    }
+
+    /// returns the id of the unit standing on a tile
+    pub fn occupant(&self, pos: Position) -> Option<u64> {
+        self.tile(pos)?.occupant()
+    }
+
+    /// puts a unit on a tile, or clears it with `None`
+    pub fn set_occupant(&mut self, pos: Position, occupant: Option<u64>) {
+        if let Some(tile) = self.tile_mut(pos) {
+            tile.set_occupant(occupant);
+        }
+    }
+
+    /// returns if a unit could step onto a tile right now
+    pub fn is_open(&self, pos: Position) -> bool {
+        self.tile(pos).is_some_and(|t| t.is_open())
+    }
+
+    /// returns the tile one step from `pos` in direction `mov`, if it's on
+    /// the board and the board lets units move that way
+    pub fn step(&self, pos: Position, mov: Movement) -> Option<Position> {
+        if !self.directions().contains(&mov) {
+            return None;
+        }
+        let (dx, dy) = self.topology.offset(mov)?;
+        pos.offset(dx, dy).filter(|p| self.contains(*p))
+    }
+
+    /// returns if the rules on squeezing past cover stop a diagonal step
+    /// from `pos` in direction `mov`
+    pub fn corner_blocked(&self, pos: Position, mov: Movement) -> bool {
+        if !self.is_diagonal(mov) {
+            return false;
+        }
+        let (dx, dy) = match self.topology.offset(mov) {
+            Some(a) => a,
+            None => return false
+        };
+        // the two squares the diagonal passes between
+        let blocked = |dx, dy| pos.offset(dx, dy)
+            .and_then(|p| self.tile(p))
+            .is_some_and(|t| !t.terrain().passable());
+        let (a, b) = (blocked(dx, 0), blocked(0, dy));
+        match self.diagonals {
+            Diagonals::Off | Diagonals::Free => false,
+            Diagonals::NoSqueeze => a && b,
+            Diagonals::NoCorners => a || b
+        }
+    }
+
+    /// goes through the tiles next to `pos` a unit could step to, going by
+    /// the board's rules, along with the direction to each
+    pub fn neighbours(&self, pos: Position) -> impl Iterator<Item = (Movement, Position)> + '_ {
+        self.directions()
+            .iter()
+            .filter(move |mov| !self.corner_blocked(pos, **mov))
+            .filter_map(move |mov| self.step(pos, *mov).map(|p| (*mov, p)))
+    }
+
+    /// returns what a step in direction `mov` costs on average, in half moves
+    pub fn step_half_moves(&self, mov: Movement) -> u64 {
+        if self.is_diagonal(mov) {
+            self.metric.diagonal_half_moves()
+        } else {
+            2
+        }
+    }
+
+    /// returns how far apart two tiles are
+    pub fn distance(&self, from: Position, to: Position) -> f64 {
+        self.topology.distance(self.metric, from, to)
+    }
+
+    /// returns if nothing on the tiles between two tiles blocks sight
+    pub fn line_of_sight(&self, from: Position, to: Position) -> bool {
+        self.topology.line(from, to)
+            .into_iter()
+            .filter(|pos| *pos != to)
+            .all(|pos| !self.tile(pos).is_some_and(|t| t.terrain().blocks_sight()))
+    }
+
+    /// goes through every position on the board, a row at a time
+    pub fn positions(&self) -> impl Iterator<Item = Position> {
+        let cols = self.cols;
+        (0..self.rows).flat_map(move |x| (0..cols).map(move |y| Position::new(x, y)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// an open map of the given size with the other settings left at their defaults
+    fn board(width: u64, height: u64) -> Board {
+        Board::new(&MapSettings {
+            width,
+            height,
+            ..MapSettings::default()
+        })
+    }
+
+    #[test]
+    fn map_sizes_are_bounded() {
+        let wide = MapSettings { width: 300, height: 20, ..MapSettings::default() };
+        assert!(wide.validate().is_ok());
+        let thin = MapSettings { width: MIN_MAP_SIZE - 1, ..MapSettings::default() };
+        assert!(thin.validate().is_err());
+        let huge = MapSettings { height: MAX_MAP_SIZE + 1, ..MapSettings::default() };
+        assert!(huge.validate().is_err());
+    }
+
+    #[test]
+    fn rectangular_maps_have_rows_up_and_columns_across() {
+        let board = board(30, 5);
+        assert_eq!((board.rows(), board.cols()), (5, 30));
+        assert!(board.contains(Position::new(4, 29)));
+        assert!(!board.contains(Position::new(5, 0)));
+        assert!(!board.contains(Position::new(0, 30)));
+        assert_eq!(board.positions().count(), 150);
+    }
+
+    #[test]
+    fn changing_a_tile_leaves_the_rest_and_other_copies_alone() {
+        let mut board = board(6, 4);
+        let copy = board.clone();
+        let pos = Position::new(2, 3);
+        board.tile_mut(pos).unwrap().set_terrain(Terrain::Wall);
+
+        assert!(!board.is_open(pos));
+        assert!(copy.is_open(pos));
+        assert_eq!(board.positions().filter(|p| !board.is_open(*p)).count(), 1);
+        assert!(board.tile_mut(Position::new(4, 0)).is_none());
+    }
+
+    #[test]
+    fn hex_tiles_have_six_neighbours_and_no_up_or_down() {
+        let board = Board::new(&MapSettings { grid: Topology::Hex, ..MapSettings::default() });
+        assert_eq!(board.neighbours(Position::new(5, 5)).count(), 6);
+        assert_eq!(board.neighbours(Position::new(0, 0)).count(), 2);
+        assert!(board.step(Position::new(5, 5), Movement::Up).is_none());
+        assert!(board.step(Position::new(5, 5), Movement::UpRight) == Some(Position::new(6, 5)));
+    }
+
+    #[test]
+    fn corner_rules_decide_which_diagonals_get_past_cover() {
+        let pos = Position::new(5, 5);
+        let with = |diagonals, walls: &[Position]| {
+            let mut board = Board::new(&MapSettings { diagonals, ..MapSettings::default() });
+            for wall in walls {
+                board.tile_mut(*wall).unwrap().set_terrain(Terrain::Wall);
+            }
+            board
+        };
+        // cover above and to the right of the tile, either side of up and right
+        let one = [Position::new(6, 5)];
+        let both = [Position::new(6, 5), Position::new(5, 6)];
+
+        assert!(!with(Diagonals::Free, &both).corner_blocked(pos, Movement::UpRight));
+        assert!(!with(Diagonals::NoSqueeze, &one).corner_blocked(pos, Movement::UpRight));
+        assert!(with(Diagonals::NoSqueeze, &both).corner_blocked(pos, Movement::UpRight));
+        assert!(with(Diagonals::NoCorners, &one).corner_blocked(pos, Movement::UpRight));
+        assert!(!with(Diagonals::NoCorners, &one).corner_blocked(pos, Movement::DownLeft));
+        // straight steps never squeeze past anything
+        assert!(!with(Diagonals::NoCorners, &both).corner_blocked(pos, Movement::Left));
+    }
+
+    #[test]
+    fn diagonal_steps_follow_the_rules_and_cost_by_the_metric() {
+        let off = board(10, 10);
+        assert!(off.step(Position::new(5, 5), Movement::UpRight).is_none());
+        assert_eq!(off.neighbours(Position::new(5, 5)).count(), 4);
+
+        let free = Board::new(&MapSettings { diagonals: Diagonals::Free, ..MapSettings::default() });
+        assert!(free.step(Position::new(5, 5), Movement::UpRight) == Some(Position::new(6, 6)));
+        assert_eq!(free.neighbours(Position::new(5, 5)).count(), 8);
+        assert_eq!(free.step_half_moves(Movement::UpRight), 3);
+        assert_eq!(free.step_half_moves(Movement::Up), 2);
+    }
+}
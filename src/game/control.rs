@@ -0,0 +1,127 @@
+// defines control points that teams fight over for score
+
+use super::{
+    Board,
+    Position,
+    Team,
+    Units
+};
+
+/// how far from a control point units count towards capturing it
+pub const DEFAULT_CONTROL_RADIUS: f64 = 2f64;
+
+/// a tile that scores for whichever team controls it
+#[derive(Clone)]
+pub struct ControlPoint {
+    position:   Position,
+    owner:      Option<Team>
+}
+
+impl ControlPoint {
+    /// creates a new unowned control point
+    pub fn new(position: Position) -> Self {
+        ControlPoint {
+            position,
+            owner: None
+        }
+    }
+
+    /// returns the position of the control point
+    pub fn position(&self) -> Position {
+        self.position
+    }
+
+    /// returns the team that controls the point
+    pub fn owner(&self) -> Option<Team> {
+        self.owner
+    }
+
+    /// returns the glyph drawn for the point, lower case of the owning team
+    pub fn glyph(&self) -> char {
+        match self.owner {
+            Some(Team::Redfor) => 'r',
+            Some(Team::Bluefor) => 'b',
+            None => 'o'
+        }
+    }
+
+    /// hands the point to a team if it has the only units within `radius`,
+    /// leaving it alone if it is empty or contested
+    pub fn contest(&mut self, board: &Board, units: &Units, radius: f64) {
+        let mut nearby = units.iter()
+            .filter(|u| board.distance(u.position(), self.position) <= radius)
+            .map(|u| u.team());
+
+        if let Some(team) = nearby.next() {
+            if nearby.all(|t| t == team) {
+                self.owner = Some(team);
+            }
+        }
+    }
+}
+
+/// display format implementation
+impl std::fmt::Display for ControlPoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.owner {
+            Some(team) => write!(f, "{} ({})", self.position, team),
+            None => write!(f, "{} (nobody)", self.position)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{
+        Unit,
+        UnitStats
+    };
+
+    fn units(placed: &[(Team, Position)]) -> Units {
+        let mut units = Units::new();
+        for (id, (team, pos)) in placed.iter().enumerate() {
+            units.insert(Unit::from_stats(format!("Unit #{}", id), id as u64, *team, &UnitStats::default(), *pos));
+        }
+        units
+    }
+
+    #[test]
+    fn the_only_team_in_range_captures_the_point() {
+        let board = Board::new(&Default::default());
+        let mut point = ControlPoint::new(Position::new(5, 5));
+        // 3 tiles away is out of the default radius
+        point.contest(&board, &units(&[(Team::Redfor, Position::new(5, 8))]), DEFAULT_CONTROL_RADIUS);
+        assert!(point.owner().is_none());
+        assert_eq!(point.glyph(), 'o');
+
+        point.contest(&board, &units(&[(Team::Redfor, Position::new(6, 6))]), DEFAULT_CONTROL_RADIUS);
+        assert!(point.owner() == Some(Team::Redfor));
+        assert_eq!(point.glyph(), 'r');
+        assert_eq!(point.to_string(), "5x5 (Redfor)");
+    }
+
+    #[test]
+    fn contested_or_empty_points_keep_their_owner() {
+        let board = Board::new(&Default::default());
+        let mut point = ControlPoint::new(Position::new(5, 5));
+        point.contest(&board, &units(&[(Team::Bluefor, Position::new(5, 5))]), DEFAULT_CONTROL_RADIUS);
+
+        let both = units(&[(Team::Bluefor, Position::new(5, 6)), (Team::Redfor, Position::new(4, 5))]);
+        point.contest(&board, &both, DEFAULT_CONTROL_RADIUS);
+        assert!(point.owner() == Some(Team::Bluefor));
+        point.contest(&board, &Units::new(), DEFAULT_CONTROL_RADIUS);
+        assert!(point.owner() == Some(Team::Bluefor));
+    }
+
+    #[test]
+    fn the_radius_decides_who_is_close_enough() {
+        let board = Board::new(&Default::default());
+        let mut point = ControlPoint::new(Position::new(5, 5));
+        let far = units(&[(Team::Redfor, Position::new(5, 9))]);
+        point.contest(&board, &far, 3f64);
+        assert!(point.owner().is_none());
+        point.contest(&board, &far, 4f64);
+        assert!(point.owner() == Some(Team::Redfor));
+    }
+}
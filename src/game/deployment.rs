@@ -0,0 +1,144 @@
+// defines how the teams set their units up on the map before the battle
+
+use std::ops::Range;
+
+use super::{
+    Board,
+    Position,
+    Team
+};
+
+/// how many rows deep each team's deployment zone is, unless the team needs
+/// more room or the map is too small
+pub const DEPLOY_ROWS: usize = 3;
+
+/// how the teams place their units before the battle starts
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum DeployMode {
+    /// line up along the team's edge of the map without asking anyone
+    Lineup,
+    /// take turns placing one unit at a time, seeing what the other team did
+    Alternate,
+    /// each team places all of its units without seeing the other team's
+    Hidden
+}
+
+/// the state of the deployment phase: who is still to be placed and where
+/// each team is allowed to put them
+pub struct Deployment {
+    mode:       DeployMode,
+    /// units still to be placed, in the order they go
+    waiting:    Vec<(u64, Team)>,
+    red_rows:   Range<usize>,
+    blue_rows:  Range<usize>
+}
+
+impl Deployment {
+    /// sets up deployment for the given units, Redfor placing first. each
+    /// team's zone is the rows along its own edge of the map
+    pub fn new(mode: DeployMode, board: &Board, units: &[(u64, Team)]) -> Self {
+        let (red, blue): (Vec<_>, Vec<_>) = units.iter().partition(|(_, team)| *team == Team::Redfor);
+        let waiting = match mode {
+            DeployMode::Lineup => Vec::new(),
+            DeployMode::Hidden => red.iter().chain(blue.iter()).copied().collect(),
+            DeployMode::Alternate => {
+                let mut waiting = Vec::new();
+                for i in 0..red.len().max(blue.len()) {
+                    waiting.extend(red.get(i));
+                    waiting.extend(blue.get(i));
+                }
+                waiting
+            }
+        };
+
+        let rows = board.rows();
+        let depth = |size: usize| DEPLOY_ROWS.max(size.div_ceil(board.cols())).min(rows / 2);
+        Deployment {
+            mode,
+            waiting,
+            red_rows: 0..depth(red.len()),
+            blue_rows: rows - depth(blue.len())..rows
+        }
+    }
+
+    /// returns the unit that gets placed next, `None` once everyone is down
+    pub fn next(&self) -> Option<u64> {
+        self.waiting.first().map(|(id, _)| *id)
+    }
+
+    /// returns the team placing a unit next
+    pub fn placing(&self) -> Option<Team> {
+        self.waiting.first().map(|(_, team)| *team)
+    }
+
+    /// returns if the unit is yet to be placed
+    pub fn is_waiting(&self, id: u64) -> bool {
+        self.waiting.iter().any(|(u, _)| *u == id)
+    }
+
+    /// returns the units of a team yet to be placed, in the order they go
+    pub fn waiting_for(&self, team: Team) -> Vec<u64> {
+        self.waiting.iter()
+            .filter(|(_, t)| *t == team)
+            .map(|(id, _)| *id)
+            .collect()
+    }
+
+    /// marks a unit as placed
+    pub fn placed(&mut self, id: u64) {
+        self.waiting.retain(|(u, _)| *u != id);
+    }
+
+    /// returns the rows a team deploys in
+    pub fn rows(&self, team: Team) -> Range<usize> {
+        match team {
+            Team::Redfor => self.red_rows.clone(),
+            Team::Bluefor => self.blue_rows.clone()
+        }
+    }
+
+    /// returns if a tile is in a team's deployment zone
+    pub fn in_zone(&self, team: Team, pos: Position) -> bool {
+        self.rows(team).contains(&pos.x())
+    }
+
+    /// returns every tile of a team's deployment zone
+    pub fn zone(&self, board: &Board, team: Team) -> Vec<Position> {
+        self.rows(team)
+            .flat_map(|x| (0..board.cols()).map(move |y| Position::new(x, y)))
+            .collect()
+    }
+
+    /// returns if a team's units are kept off the screen, which they are
+    /// while the other team is placing in secret
+    pub fn hides(&self, team: Team) -> bool {
+        self.mode == DeployMode::Hidden && self.placing().is_some_and(|t| t != team)
+    }
+}
+
+/// display format implementation
+impl std::fmt::Display for DeployMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let printable = match self {
+            DeployMode::Lineup => "lineup",
+            DeployMode::Alternate => "alternate",
+            DeployMode::Hidden => "hidden"
+        };
+
+        write!(f, "{}", printable)
+    }
+}
+
+/// parses a deployment mode from its name, ignoring case
+impl std::str::FromStr for DeployMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "lineup" => Ok(DeployMode::Lineup),
+            "alternate" => Ok(DeployMode::Alternate),
+            "hidden" => Ok(DeployMode::Hidden),
+            _ => Err(format!("'{}' is not a deployment, expected lineup, alternate or hidden", s))
+        }
+    }
+}
@@ -1,7 +1,96 @@
+use super::{
+    Movement,
+    Position
+};
+
 /// define our error enumerator
 pub enum ErrorOut {
     Success,
     SuccessIncomplete,
     NotFound,
-    FailedGeneric
-}
\ No newline at end of file
+    FailedGeneric,
+    Undone,
+    Yielded,
+    Interrupted,
+    Aborted
+}
+
+/// why the engine refused to do something
+#[derive(Clone, PartialEq, Debug)]
+pub enum GameError {
+    /// the move would take the unit off the edge of the map
+    OutOfBounds,
+    /// the map's tiles don't let units move that way
+    BadDirection(Movement),
+    /// cover on the corners stops the unit cutting across diagonally
+    CornerBlocked(Movement),
+    /// the tile isn't in the unit's deployment zone
+    OutsideDeployment(Position),
+    /// terrain is in the way on the tile
+    BlockedByTerrain(Position),
+    /// another unit is already standing on the tile
+    OccupiedByUnit {
+        name:       String,
+        position:   Position
+    },
+    /// the target is further away than the action reaches
+    OutOfRange {
+        distance:   f64,
+        range:      f64
+    },
+    /// terrain between the unit and its target blocks the shot
+    NoLineOfSight,
+    /// no unit with the id is in the game
+    UnknownUnit(u64),
+    /// the unit isn't the one whose turn it is
+    NotYourTurn,
+    /// the unit can't afford the action
+    NotEnoughPoints {
+        cost:       u64,
+        left:       u64
+    },
+    /// the unit doesn't carry the item asked for
+    NoSuchItem,
+    /// the unit doesn't have the ability asked for
+    NoSuchAbility,
+    /// the ability has been used up for this battle
+    NoUsesLeft(String),
+    /// the item or ability only works on a friendly unit
+    NeedsAlly(String),
+    /// there is no downed friendly unit on the tile to bring back
+    NothingToRevive(Position),
+    /// the unit has already started acting this round
+    AlreadyActed,
+    /// every unit has had its turn this round
+    NobodyLeftToAct
+}
+
+/// display format implementation, the reason as shown to the player
+impl std::fmt::Display for GameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            GameError::OutOfBounds => write!(f, "That's off the edge of the map"),
+            GameError::BadDirection(mov) => write!(f, "Units can't move {} on this map", mov),
+            GameError::CornerBlocked(mov) => write!(f, "Cover on the corner is in the way of moving {}", mov),
+            GameError::OutsideDeployment(pos) => write!(f, "{} is outside of the deployment zone", pos),
+            GameError::BlockedByTerrain(pos) => write!(f, "{} is blocked by terrain", pos),
+            GameError::OccupiedByUnit { name, position } => write!(f, "{} is standing on {}", name, position),
+            GameError::OutOfRange { distance, range } =>
+                write!(f, "Target out of range ({:.1} tiles away, reaches {})", distance, range),
+            GameError::NoLineOfSight => write!(f, "Terrain blocks the line of sight"),
+            GameError::UnknownUnit(id) => write!(f, "No unit with ID {} found", id),
+            GameError::NotYourTurn => write!(f, "It's not that unit's turn"),
+            GameError::NotEnoughPoints { cost, left } =>
+                write!(f, "Not enough action points (needs {} AP, {} AP left)", cost, left),
+            GameError::NoSuchItem => write!(f, "No such item"),
+            GameError::NoSuchAbility => write!(f, "No such ability"),
+            GameError::NoUsesLeft(name) => write!(f, "{} has no uses left", name),
+            GameError::NeedsAlly(name) => write!(f, "{} needs a friendly unit", name),
+            GameError::NothingToRevive(pos) => write!(f, "No downed ally at {}", pos),
+            GameError::AlreadyActed => write!(f, "Can only delay before acting"),
+            GameError::NobodyLeftToAct => write!(f, "Nobody else is left to act this round")
+        }
+    }
+}
+
+impl std::error::Error for GameError {}
@@ -0,0 +1,222 @@
+// defines the events the game emits and the bus that hands them out to subscribers
+
+use super::{
+    BuffKind,
+    Item,
+    Outcome,
+    Position,
+    Stat,
+    Team
+};
+
+/// how an attack on a unit turned out
+#[derive(Clone)]
+pub enum AttackResult {
+    /// the attack landed and the target is left with `health`
+    Hit {
+        health: u64
+    },
+    /// the attack landed and finished the target off
+    Killed,
+    Missed
+}
+
+/// something that happened in the game
+#[derive(Clone)]
+pub enum Event {
+    /// a unit started its activation
+    TurnStarted {
+        unit_id:    u64,
+        name:       String,
+        team:       Team,
+        round:      u64
+    },
+    /// a unit moved a tile
+    UnitMoved {
+        unit_id:    u64,
+        name:       String,
+        from:       Position,
+        to:         Position
+    },
+    /// an attack, reaction shot or blast was resolved against a unit
+    AttackResolved {
+        attacker_id: u64,
+        attacker:   String,
+        target_id:  u64,
+        target:     String,
+        attack:     String,
+        damage:     u64,
+        result:     AttackResult,
+        reaction:   bool
+    },
+    /// a unit went down
+    UnitDied {
+        unit_id:    u64,
+        name:       String,
+        team:       Team,
+        position:   Position
+    },
+    /// a unit used an item on a tile
+    ItemUsed {
+        unit_id:    u64,
+        name:       String,
+        item:       Item,
+        tile:       Position
+    },
+    /// a unit picked up an item lying on its tile
+    ItemPickedUp {
+        unit_id:    u64,
+        name:       String,
+        item:       Item,
+        tile:       Position
+    },
+    /// a unit got some of its hp back
+    UnitHealed {
+        unit_id:    u64,
+        name:       String,
+        health:     u64,
+        max_health: u64
+    },
+    /// a downed unit was brought back
+    UnitRevived {
+        unit_id:    u64,
+        name:       String,
+        health:     u64
+    },
+    /// a unit's stats were boosted, until the end of `last_round` or just
+    /// for its next attack if there isn't one
+    BuffApplied {
+        unit_id:    u64,
+        name:       String,
+        kind:       BuffKind,
+        amount:     u64,
+        last_round: Option<u64>
+    },
+    /// a unit went up a level
+    LevelUp {
+        unit_id:    u64,
+        name:       String,
+        level:      u64,
+        bonuses:    Vec<(Stat, u64)>,
+        can_promote: bool
+    },
+    /// every unit has acted and the round was scored
+    RoundEnded {
+        round:      u64
+    },
+    /// a new unit joined the battle
+    UnitSpawned {
+        unit_id:    u64,
+        name:       String,
+        team:       Team,
+        position:   Position
+    },
+    /// text for the players, e.g. a mission briefing
+    Message {
+        text:       String
+    },
+    /// everything since the last commit became one step of the history
+    Committed,
+    /// everything since the last commit joined the last step of the
+    /// history, as the start of a round does
+    Amended,
+    /// the last step of the history was taken back, along with anything
+    /// since the last commit
+    Undone,
+    /// the last undone step was played again
+    Redone,
+    /// the match is over
+    GameOver {
+        outcome:    Outcome
+    }
+}
+
+/// something that wants to hear about what happens in the game
+pub trait Subscriber {
+    /// called with every event the game emits, in order
+    fn notify(&mut self, event: &Event);
+}
+
+/// hands the game's events out to everyone subscribed
+pub struct EventBus {
+    subscribers: Vec<Box<dyn Subscriber>>
+}
+
+impl EventBus {
+    /// creates a bus with nobody listening
+    pub fn new() -> Self {
+        EventBus {
+            subscribers: Vec::new()
+        }
+    }
+
+    /// adds a subscriber that will hear about every event from now on
+    pub fn subscribe(&mut self, subscriber: Box<dyn Subscriber>) {
+        self.subscribers.push(subscriber);
+    }
+
+    /// sends an event to every subscriber
+    pub fn emit(&mut self, event: Event) {
+        for subscriber in self.subscribers.iter_mut() {
+            subscriber.notify(&event);
+        }
+    }
+}
+
+/// display format implementation, one line of `<event> key=value ...`
+impl std::fmt::Display for Event {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Event::TurnStarted { unit_id, name, team, round } =>
+                write!(f, "turn_started unit={} name={:?} team={} round={}", unit_id, name, team, round),
+            Event::UnitMoved { unit_id, name, from, to } =>
+                write!(f, "unit_moved unit={} name={:?} from={} to={}", unit_id, name, from, to),
+            Event::AttackResolved { attacker_id, attacker, target_id, target, attack, damage, result, reaction } => {
+                let result = match result {
+                    AttackResult::Hit { health } => format!("hit health={}", health),
+                    AttackResult::Killed => "killed".to_string(),
+                    AttackResult::Missed => "missed".to_string()
+                };
+                write!(
+                    f,
+                    "attack_resolved attacker={} attacker_name={:?} target={} target_name={:?} attack={:?} damage={} reaction={} result={}",
+                    attacker_id, attacker, target_id, target, attack, damage, reaction, result
+                )
+            },
+            Event::UnitDied { unit_id, name, team, position } =>
+                write!(f, "unit_died unit={} name={:?} team={} at={}", unit_id, name, team, position),
+            Event::ItemUsed { unit_id, name, item, tile } =>
+                write!(f, "item_used unit={} name={:?} item={:?} tile={}", unit_id, name, item.name(), tile),
+            Event::ItemPickedUp { unit_id, name, item, tile } =>
+                write!(f, "item_picked_up unit={} name={:?} item={:?} tile={}", unit_id, name, item.name(), tile),
+            Event::UnitHealed { unit_id, name, health, max_health } =>
+                write!(f, "unit_healed unit={} name={:?} health={}/{}", unit_id, name, health, max_health),
+            Event::UnitRevived { unit_id, name, health } =>
+                write!(f, "unit_revived unit={} name={:?} health={}", unit_id, name, health),
+            Event::BuffApplied { unit_id, name, kind, amount, last_round } => {
+                let until = match last_round {
+                    Some(r) => r.to_string(),
+                    None => "next_attack".to_string()
+                };
+                write!(f, "buff_applied unit={} name={:?} stat={} amount={} until={}", unit_id, name, kind, amount, until)
+            },
+            Event::LevelUp { unit_id, name, level, bonuses, can_promote } => {
+                let bonuses: Vec<String> = bonuses.iter().map(|(s, a)| format!("{}+{}", s, a)).collect();
+                write!(
+                    f,
+                    "level_up unit={} name={:?} level={} bonuses={} can_promote={}",
+                    unit_id, name, level, bonuses.join(","), can_promote
+                )
+            },
+            Event::RoundEnded { round } => write!(f, "round_ended round={}", round),
+            Event::UnitSpawned { unit_id, name, team, position } =>
+                write!(f, "unit_spawned unit={} name={:?} team={} at={}", unit_id, name, team, position),
+            Event::Message { text } => write!(f, "message text={:?}", text),
+            Event::Committed => write!(f, "committed"),
+            Event::Amended => write!(f, "amended"),
+            Event::Undone => write!(f, "undone"),
+            Event::Redone => write!(f, "redone"),
+            Event::GameOver { outcome } => write!(f, "game_over outcome={:?}", outcome.to_string())
+        }
+    }
+}
@@ -0,0 +1,259 @@
+// defines the undo/redo history of the actions taken in a game
+
+use super::{
+    Position,
+    Roll,
+    Scenario,
+    Tile,
+    Unit
+};
+
+/// a single reversible change to the game state
+#[derive(Clone)]
+pub enum Action {
+    /// a unit moved from one tile to another
+    Move {
+        unit_id:    u64,
+        from:       Position,
+        to:         Position
+    },
+    /// a unit took damage, keeping a copy of it from before the hit
+    Damage {
+        before:     Unit,
+        damage:     u64,
+        killed:     bool
+    },
+    /// a downed unit was brought back, keeping a copy of it from before and
+    /// after along with where it was in the list of downed units
+    Revive {
+        before:     Unit,
+        after:      Unit,
+        index:      usize
+    },
+    /// a new unit joined the battle
+    Spawned {
+        unit:       Unit
+    },
+    /// a unit's experience, stats or attacks changed
+    Changed {
+        before:     Unit,
+        after:      Unit
+    },
+    /// a unit's remaining and banked action points changed
+    Points {
+        unit_id:    u64,
+        before:     (u64, u64),
+        after:      (u64, u64)
+    },
+    /// a unit finished its activation
+    TurnEnded,
+    /// a new round began, keeping each unit's action points from before the refresh
+    NewRound {
+        before:     Vec<(u64, u64)>
+    },
+    /// the initiative order changed
+    Reorder {
+        before:     Vec<Roll>,
+        after:      Vec<Roll>
+    },
+    /// the terrain, items or effects of a tile changed, e.g. cover was
+    /// blown away or smoke was laid. who is standing there is left to the
+    /// actions that move units around
+    Tile {
+        position:   Position,
+        before:     Tile,
+        after:      Tile
+    },
+    /// progress towards the victory conditions changed
+    Objectives {
+        before:     Scenario,
+        after:      Scenario
+    }
+}
+
+/// keeps the actions of each completed turn so they can be undone and redone
+pub struct History {
+    pending:    Vec<Action>,
+    undo:       Vec<Vec<Action>>,
+    redo:       Vec<Vec<Action>>,
+    /// whether actions are kept at all
+    recording:  bool
+}
+
+impl History {
+    /// creates an empty history
+    pub fn new() -> Self {
+        History {
+            pending: Vec::new(),
+            undo: Vec::new(),
+            redo: Vec::new(),
+            recording: true
+        }
+    }
+
+    /// stops keeping actions and drops any kept so far, for games nobody
+    /// is going to undo
+    pub fn stop_recording(&mut self) {
+        self.recording = false;
+        self.pending.clear();
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    /// records an action as part of the turn in progress
+    pub fn record(&mut self, action: Action) {
+        if self.recording {
+            self.pending.push(action);
+        }
+    }
+
+    /// closes the turn in progress so it is undone as one step, returning
+    /// if there was anything to close
+    pub fn commit(&mut self) -> bool {
+        if self.pending.is_empty() {
+            return false;
+        }
+
+        // anything new invalidates the redo stack
+        self.redo.clear();
+        let step = std::mem::take(&mut self.pending);
+        self.undo.push(step);
+        true
+    }
+
+    /// adds the start of a round to the step that ended the last one, so
+    /// undoing goes back into that turn rather than rolling for initiative
+    /// again. the first round has nothing before it, so it's a step of its own
+    pub fn commit_round(&mut self) {
+        if !self.recording {
+            return;
+        }
+        self.redo.clear();
+        let actions = std::mem::take(&mut self.pending);
+        match self.undo.last_mut() {
+            Some(step) => step.extend(actions),
+            None => self.undo.push(actions)
+        }
+    }
+
+    /// takes the actions recorded since the last commit so they can be reverted
+    pub fn rollback(&mut self) -> Vec<Action> {
+        std::mem::take(&mut self.pending)
+    }
+
+    /// takes the last completed step, moving it to the redo stack
+    pub fn undo(&mut self) -> Option<Vec<Action>> {
+        let step = self.undo.pop()?;
+        self.redo.push(step.clone());
+        Some(step)
+    }
+
+    /// takes the last undone step, moving it back to the undo stack
+    pub fn redo(&mut self) -> Option<Vec<Action>> {
+        let step = self.redo.pop()?;
+        self.undo.push(step.clone());
+        Some(step)
+    }
+
+    /// returns if there is anything to undo
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    /// returns if there is anything to redo
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// a history with one committed step, each step being some ended turns
+    fn with_step(turns: usize) -> History {
+        let mut history = History::new();
+        for _ in 0..turns {
+            history.record(Action::TurnEnded);
+        }
+        history.commit();
+        history
+    }
+
+    #[test]
+    fn commit_needs_something_pending() {
+        let mut history = History::new();
+        assert!(!history.commit());
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn undo_and_redo_move_whole_steps() {
+        let mut history = with_step(2);
+        assert_eq!(history.undo().map(|s| s.len()), Some(2));
+        assert!(!history.can_undo());
+        assert_eq!(history.redo().map(|s| s.len()), Some(2));
+        assert!(history.can_undo());
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn commit_clears_redo() {
+        let mut history = with_step(1);
+        history.undo();
+        history.record(Action::TurnEnded);
+        history.commit();
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn rollback_takes_only_pending() {
+        let mut history = with_step(1);
+        history.record(Action::TurnEnded);
+        assert_eq!(history.rollback().len(), 1);
+        assert_eq!(history.undo().map(|s| s.len()), Some(1));
+    }
+
+    #[test]
+    fn round_start_joins_the_last_step() {
+        let mut history = with_step(1);
+        history.record(Action::NewRound { before: Vec::new() });
+        history.commit_round();
+        assert_eq!(history.undo().map(|s| s.len()), Some(2));
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn first_round_start_is_a_step_of_its_own() {
+        let mut history = History::new();
+        history.record(Action::NewRound { before: Vec::new() });
+        history.commit_round();
+        assert_eq!(history.undo().map(|s| s.len()), Some(1));
+    }
+
+    #[test]
+    fn nothing_is_kept_once_recording_stops() {
+        let mut history = with_step(1);
+        history.stop_recording();
+        assert!(!history.can_undo());
+        history.record(Action::TurnEnded);
+        assert!(!history.commit());
+        history.commit_round();
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn round_start_clears_redo() {
+        let mut history = with_step(1);
+        history.undo();
+        history.commit_round();
+        assert!(!history.can_redo());
+
+        let mut history = with_step(1);
+        history.undo();
+        history.undo();
+        history.record(Action::NewRound { before: Vec::new() });
+        history.commit_round();
+        assert!(!history.can_redo());
+    }
+}
@@ -0,0 +1,111 @@
+// defines the initiative order units act in each round
+
+use super::Team;
+
+/// die rolled on top of a unit's initiative each round
+pub const INITIATIVE_DIE: u64 = 6;
+
+/// a unit's initiative for one round
+#[derive(Clone)]
+pub struct Roll {
+    pub unit_id:    u64,
+    pub team:       Team,
+    pub initiative: u64,
+    pub roll:       u64
+}
+
+impl Roll {
+    /// returns the unit's score for the round
+    pub fn total(&self) -> u64 {
+        self.initiative + self.roll
+    }
+}
+
+/// display format implementation
+impl std::fmt::Display for Roll {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.roll > 0 {
+            write!(f, "{} + {} = {}", self.initiative, self.roll, self.total())
+        } else {
+            write!(f, "{}", self.initiative)
+        }
+    }
+}
+
+/// sorts the rolls into the order units act in, highest score first
+///
+/// ties go to alternating teams, starting with `first`, so matching units
+/// still trade activations back and forth
+pub fn initiative_order(mut rolls: Vec<Roll>, first: Team) -> Vec<Roll> {
+    rolls.sort_by(|a, b| b.total().cmp(&a.total()).then(a.unit_id.cmp(&b.unit_id)));
+
+    // rank each unit among the tied units of its own team
+    let mut ranked: Vec<(usize, Roll)> = Vec::new();
+    for roll in rolls {
+        let rank = ranked.iter()
+            .filter(|(_, r)| r.team == roll.team && r.total() == roll.total())
+            .count();
+        ranked.push((rank, roll));
+    }
+
+    ranked.sort_by(|(ra, a), (rb, b)| {
+        b.total().cmp(&a.total())
+            .then(ra.cmp(rb))
+            .then((a.team != first).cmp(&(b.team != first)))
+    });
+    ranked.into_iter().map(|(_, r)| r).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roll(unit_id: u64, team: Team, initiative: u64, roll: u64) -> Roll {
+        Roll { unit_id, team, initiative, roll }
+    }
+
+    /// the unit ids in the order they act
+    fn order(rolls: Vec<Roll>, first: Team) -> Vec<u64> {
+        initiative_order(rolls, first).iter().map(|r| r.unit_id).collect()
+    }
+
+    #[test]
+    fn highest_total_goes_first() {
+        let rolls = vec![
+            roll(0, Team::Bluefor, 10, 1),
+            roll(1, Team::Bluefor, 8, 6),
+            roll(2, Team::Redfor, 12, 0)
+        ];
+        assert_eq!(order(rolls, Team::Redfor), vec![1, 2, 0]);
+    }
+
+    #[test]
+    fn ties_alternate_teams_starting_with_the_first() {
+        let rolls = vec![
+            roll(0, Team::Bluefor, 10, 0),
+            roll(1, Team::Bluefor, 10, 0),
+            roll(2, Team::Redfor, 10, 0),
+            roll(3, Team::Redfor, 10, 0)
+        ];
+        assert_eq!(order(rolls.clone(), Team::Redfor), vec![2, 0, 3, 1]);
+        assert_eq!(order(rolls, Team::Bluefor), vec![0, 2, 1, 3]);
+    }
+
+    #[test]
+    fn a_team_with_more_tied_units_finishes_the_tie() {
+        let rolls = vec![
+            roll(0, Team::Bluefor, 9, 1),
+            roll(1, Team::Bluefor, 10, 0),
+            roll(2, Team::Bluefor, 7, 3),
+            roll(3, Team::Redfor, 5, 5),
+            roll(4, Team::Redfor, 12, 0)
+        ];
+        assert_eq!(order(rolls, Team::Redfor), vec![4, 3, 0, 1, 2]);
+    }
+
+    #[test]
+    fn rolls_show_how_the_total_was_made() {
+        assert_eq!(roll(0, Team::Bluefor, 10, 4).to_string(), "10 + 4 = 14");
+        assert_eq!(roll(0, Team::Bluefor, 10, 0).to_string(), "10");
+    }
+}
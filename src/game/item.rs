@@ -0,0 +1,124 @@
+// defines the items units carry and what using them does
+
+/// accuracy taken off attacks against a unit standing in smoke
+pub const SMOKE_PENALTY: u64 = 40;
+
+/// what an item can be used on
+#[derive(Clone, Copy, PartialEq)]
+pub enum ItemTarget {
+    /// the unit carrying it or a friendly unit within reach
+    Ally,
+    /// any tile within reach
+    Tile
+}
+
+/// what happens when an item is used
+#[derive(Clone, Copy, PartialEq)]
+pub enum Effect {
+    /// restores hp
+    Heal(u64),
+    /// damages everything within `radius` of the tile and blows away cover
+    Blast {
+        damage: u64,
+        radius: f64
+    },
+    /// fills the tiles within `radius` with smoke for a number of rounds
+    Smoke {
+        radius: f64,
+        rounds: u64
+    },
+    /// adds damage to the unit's next attack
+    Load(u64)
+}
+
+/// an item a unit can carry, use or hand over
+#[derive(Clone)]
+pub struct Item {
+    name:   String,
+    effect: Effect,
+    target: ItemTarget,
+    range:  f64,
+    cost:   u64
+}
+
+impl Item {
+    /// creates a new item
+    pub fn new(name: String, effect: Effect, target: ItemTarget, range: f64, cost: u64) -> Self {
+        Item {
+            name,
+            effect,
+            target,
+            range,
+            cost
+        }
+    }
+
+    /// returns the item with the given name, if it is one of the stock items
+    pub fn by_name(name: &str) -> Option<Self> {
+        match name {
+            "Medkit" => Some(Item::new(name.to_string(), Effect::Heal(40), ItemTarget::Ally, 1f64, 2)),
+            "Grenade" => Some(Item::new(
+                name.to_string(),
+                Effect::Blast { damage: 40, radius: 1f64 },
+                ItemTarget::Tile,
+                5f64,
+                3
+            )),
+            "Smoke" => Some(Item::new(
+                name.to_string(),
+                Effect::Smoke { radius: 1f64, rounds: 2 },
+                ItemTarget::Tile,
+                5f64,
+                2
+            )),
+            "Ammo" => Some(Item::new(name.to_string(), Effect::Load(15), ItemTarget::Ally, 1f64, 1)),
+            _ => None
+        }
+    }
+
+    /// returns the items every unit starts with
+    pub fn default_kit() -> Vec<Self> {
+        ["Medkit", "Grenade", "Smoke", "Ammo"]
+            .iter()
+            .filter_map(|n| Self::by_name(n))
+            .collect()
+    }
+
+    /// returns the name of the item
+    pub fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    /// returns what the item does
+    pub fn effect(&self) -> Effect {
+        self.effect
+    }
+
+    /// returns what the item can be used on
+    pub fn target(&self) -> ItemTarget {
+        self.target
+    }
+
+    /// returns how far away the item can be used
+    pub fn range(&self) -> f64 {
+        self.range
+    }
+
+    /// returns the action points it takes to use the item
+    pub fn cost(&self) -> u64 {
+        self.cost
+    }
+}
+
+/// display format implementation
+impl std::fmt::Display for Item {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let effect = match self.effect {
+            Effect::Heal(hp) => format!("heals {} hp", hp),
+            Effect::Blast { damage, radius } => format!("{} dmg within {}", damage, radius),
+            Effect::Smoke { radius, rounds } => format!("smoke within {} for {} rounds", radius, rounds),
+            Effect::Load(damage) => format!("+{} dmg on the next attack", damage)
+        };
+        write!(f, "{} ({}, range {}, {} AP)", self.name, effect, self.range, self.cost)
+    }
+}
@@ -4,13 +4,171 @@ mod team;
 mod attack;
 mod position;
 mod errors;
+mod history;
+mod initiative;
+mod victory;
+mod control;
+mod roster;
+mod skills;
+mod item;
+mod ability;
+mod events;
+mod units;
+mod board;
+mod viewport;
+mod topology;
+mod deployment;
+mod army;
 pub use team::Team;
 pub use attack::Attack;
 pub use position::{
     Position,
     Movement
 };
-pub use errors::ErrorOut;
+pub use errors::{
+    ErrorOut,
+    GameError
+};
+pub use history::{
+    Action,
+    History
+};
+pub use initiative::{
+    Roll,
+    initiative_order,
+    INITIATIVE_DIE
+};
+pub use control::{
+    ControlPoint,
+    DEFAULT_CONTROL_RADIUS
+};
+pub use roster::Roster;
+pub use army::{
+    Army,
+    ArmyList,
+    Pick
+};
+pub use units::Units;
+pub use board::{
+    Board,
+    Terrain,
+    Tile,
+    TileEffect,
+    MapSettings
+};
+pub use topology::{
+    Diagonals,
+    Metric,
+    Topology
+};
+pub use deployment::{
+    Deployment,
+    DeployMode
+};
+pub use viewport::{
+    Minimap,
+    Viewport
+};
+pub use events::{
+    AttackResult,
+    Event,
+    EventBus,
+    Subscriber
+};
+pub use ability::{
+    Ability,
+    AbilityEffect,
+    Buff,
+    BuffKind
+};
+pub use item::{
+    Effect,
+    Item,
+    ItemTarget,
+    SMOKE_PENALTY
+};
+pub use skills::{
+    SkillTree,
+    Promotion,
+    Stat,
+    KILL_XP
+};
+pub use victory::{
+    Condition,
+    Outcome,
+    Scenario
+};
+
+/// action points each unit gets at the top of the round
+pub const DEFAULT_ACTION_POINTS: u64 = 4;
+/// action points it costs to move a single tile
+pub const MOVE_COST: u64 = 1;
+/// damage soaked up by each banked action point
+pub const DEFENSE_PER_POINT: u64 = 5;
+/// initiative of a default unit
+pub const DEFAULT_INITIATIVE: u64 = 10;
+/// action points it costs to hand an item to an ally
+pub const GIVE_COST: u64 = 1;
+/// chance out of 100 that a default unit's attacks hit
+pub const DEFAULT_ACCURACY: u64 = 85;
+/// hp of a default unit
+pub const DEFAULT_HEALTH: u64 = 100;
+
+/// the stats a unit starts a battle with
+#[derive(Clone)]
+pub struct UnitStats {
+    pub health:         u64,
+    pub initiative:     u64,
+    /// chance out of 100 that its attacks hit
+    pub accuracy:       u64,
+    /// action points it gets each round
+    pub action_points:  u64,
+    pub attacks:        Vec<Attack>
+}
+
+impl Default for UnitStats {
+    fn default() -> Self {
+        UnitStats {
+            health: DEFAULT_HEALTH,
+            initiative: DEFAULT_INITIATIVE,
+            accuracy: DEFAULT_ACCURACY,
+            action_points: DEFAULT_ACTION_POINTS,
+            attacks: vec![
+                Attack::new("Fight".to_string(), 50, 1f64, 2),
+                Attack::new("Shoot".to_string(), 30, 10f64, 3)
+            ]
+        }
+    }
+}
+
+impl UnitStats {
+    /// makes sure a unit with these stats could fight
+    pub fn validate(&self) -> Result<(), String> {
+        if self.health == 0 {
+            return Err("Units need some health".to_string());
+        }
+        if self.accuracy > 100 {
+            return Err(format!("Accuracy is out of 100, not {}", self.accuracy));
+        }
+        if self.action_points == 0 {
+            return Err("Units need some action points".to_string());
+        }
+        if self.attacks.is_empty() {
+            return Err("Units need at least one attack".to_string());
+        }
+        // abilities dearer than a round's points just can't be used, but an
+        // attack nobody could ever afford is a mistake
+        if let Some(attack) = self.attacks.iter().find(|a| a.cost() > self.action_points) {
+            return Err(format!(
+                "{} costs {} action points, more than the {} units get a round",
+                attack.name(),
+                attack.cost(),
+                self.action_points
+            ));
+        }
+        Ok(())
+    }
+}
 
 /// defines a unit 
 #[derive(Clone)]
@@ -19,14 +177,28 @@ pub struct Unit{
     entity_id:      u64,
     team:           Team,
     health:         u64,
+    max_health:     u64,
+    initiative:     u64,
     attacks:        Vec<Attack>,
     position:       Position,
-    action_count:   u64
+    action_points:  u64,
+    max_points:     u64,
+    banked_points:  u64,
+    experience:     u64,
+    accuracy:       u64,
+    range_bonus:    u64,
+    promotions:     Vec<String>,
+    items:          Vec<Item>,
+    loaded:         u64,
+    abilities:      Vec<Ability>,
+    buffs:          Vec<Buff>,
+    overwatch:      Option<Attack>,
+    diagonal_steps: u64
 }
 
 impl std::fmt::Display for Unit {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "[{}] {} - {} hp", self.team, self.name, self.health)
+        write!(f, "[{}] {} (lvl {}) - {} hp", self.team, self.name, self.level(), self.health)
     }
 }
 
@@ -34,7 +206,8 @@ impl std::fmt::Display for Unit {
 /// enum for returning if a unit died while dealing damage
 pub enum DamageStatus {
     Alive,
-    Dead
+    Dead,
+    Missed
 }
 
 // begin implementing stuff for our units
@@ -45,66 +218,66 @@ impl Unit {
         entity_id:  u64,
         team:       Team,
         health:     u64,
+        initiative: u64,
         attacks:    Vec<Attack>,
         position:   Position,
     ) -> Self {
-        let action_count = 0u64;
         let mut attacks = attacks.clone();
         attacks.push(
             Attack::new(
                 "Cancel".to_string(),
                 0,
-                0f64
+                0f64,
+                0
             )
         );
+        // points are handed out at the top of each round
         Unit {
             name,
             entity_id,
             team,
             health,
+            max_health: health,
+            initiative,
             attacks,
             position,
-            action_count
+            action_points: 0,
+            max_points: DEFAULT_ACTION_POINTS,
+            banked_points: 0,
+            experience: 0,
+            accuracy: DEFAULT_ACCURACY,
+            range_bonus: 0,
+            promotions: Vec::new(),
+            items: Vec::new(),
+            loaded: 0,
+            abilities: Ability::default_set(),
+            buffs: Vec::new(),
+            overwatch: None,
+            diagonal_steps: 0
         }
     }
 
-    /// creates a new unit with default properties 
-    pub fn new_default(
+    /// creates a new unit with the given stats and the standard kit
+    pub fn from_stats(
         name:       String,
         entity_id:  u64,
         team:       Team,
+        stats:      &UnitStats,
         position:   Position
     ) -> Self {
-        let health = 100;
-        let action_count = 0u64;
-        let mut attacks: Vec<Attack> = Vec::new();
-        attacks.push(Attack::new(
-            "Fight".to_string(),
-            50,
-            1f64
-        ));
-        attacks.push(Attack::new(
-            "Shoot".to_string(),
-            30,
-            10f64
-        ));
-        attacks.push(
-            Attack::new(
-                "Cancel".to_string(),
-                0,
-                0f64
-            )
-        );
-
-        Unit {
+        let mut unit = Unit::new(
             name,
             entity_id,
             team,
-            health,
-            attacks,
-            position,
-            action_count
-        }
+            stats.health,
+            stats.initiative,
+            stats.attacks.clone(),
+            position
+        );
+        unit.accuracy = stats.accuracy;
+        unit.max_points = stats.action_points;
+        unit.items = Item::default_kit();
+        unit
     }
 
     /// returns the position of the unit
@@ -122,14 +295,35 @@ impl Unit {
         self.team
     }
 
-    /// returns the action count of the unit 
-    pub fn action_count(&self) -> u64 {
-        self.action_count
+    /// returns the initiative of the unit
+    pub fn initiative(&self) -> u64 {
+        self.initiative
+    }
+
+    /// returns the action points the unit has left this round
+    pub fn action_points(&self) -> u64 {
+        self.action_points
+    }
+
+    /// returns the action points the unit gets each round
+    pub fn max_action_points(&self) -> u64 {
+        self.max_points
     }
-    
-    /// updates the action count of the unit 
-    pub fn inc_action_count(&mut self) {
-        self.action_count += 1;
+
+    /// returns the action points the unit has banked
+    pub fn banked_points(&self) -> u64 {
+        self.banked_points
+    }
+
+    /// returns if the unit has started acting this round but not finished
+    pub fn is_mid_activation(&self) -> bool {
+        self.action_points > 0 && self.action_points < self.max_points
+    }
+
+    /// sets the unit's remaining and banked action points
+    pub fn set_points(&mut self, action_points: u64, banked_points: u64) {
+        self.action_points = action_points;
+        self.banked_points = banked_points;
     }
 
     /// returns the name of the unit 
@@ -137,9 +331,18 @@ impl Unit {
         self.name.clone()
     }
 
-    /// returns the name of the unit 
-    pub fn attacks(&self) -> Vec<Attack> {
-        self.attacks.clone()
+    /// returns the attacks of the unit
+    pub fn attacks(&self) -> &[Attack] {
+        &self.attacks
+    }
+
+    /// changes the damage of the unit's attack called `name`, if it has one
+    pub fn set_attack_damage(&mut self, name: &str, damage: u64) {
+        for attack in self.attacks.iter_mut() {
+            if attack.name() == name {
+                *attack = Attack::new(attack.name(), damage, attack.range(), attack.cost());
+            }
+        }
     }
 
     /// returns the hp of the unit 
@@ -147,8 +350,186 @@ impl Unit {
         self.health
     }
 
+    /// returns the experience the unit has earned
+    pub fn experience(&self) -> u64 {
+        self.experience
+    }
+
+    /// returns the level of the unit, starting at 1
+    pub fn level(&self) -> u64 {
+        1 + self.experience / skills::XP_PER_LEVEL
+    }
+
+    /// returns the chance out of 100 that the unit's attacks hit
+    pub fn accuracy(&self) -> u64 {
+        self.accuracy
+    }
+
+    /// returns the extra range the unit gets on every attack
+    pub fn range_bonus(&self) -> u64 {
+        self.range_bonus
+    }
+
+    /// returns the names of the promotions the unit has taken
+    pub fn promotions(&self) -> Vec<String> {
+        self.promotions.clone()
+    }
+
+    /// returns how far the unit can reach with an attack
+    pub fn attack_range(&self, attack: &Attack) -> f64 {
+        attack.range() + self.range_bonus as f64
+    }
+
+    /// sets the unit's experience and what it has gained from it, used when
+    /// bringing a unit back from a saved roster
+    pub fn set_progress(&mut self, experience: u64, accuracy: u64, range_bonus: u64, promotions: Vec<String>) {
+        self.experience = experience;
+        self.accuracy = accuracy;
+        self.range_bonus = range_bonus;
+        self.promotions = promotions;
+    }
+
+    /// gives the unit experience, applying the skill tree's bonuses for
+    /// every level it goes up. returns the levels it reached
+    pub fn gain_experience(&mut self, amount: u64, tree: &SkillTree) -> Vec<u64> {
+        let old_level = self.level();
+        self.experience += amount;
+
+        let reached: Vec<u64> = (old_level + 1..=self.level()).collect();
+        for level in &reached {
+            for bonus in tree.bonuses(*level) {
+                match bonus.stat {
+                    Stat::Health => {
+                        self.max_health += bonus.amount;
+                        self.health += bonus.amount;
+                    },
+                    Stat::Accuracy => self.accuracy = (self.accuracy + bonus.amount).min(100),
+                    Stat::Range => self.range_bonus += bonus.amount
+                }
+            }
+        }
+        reached
+    }
+
+    /// takes a promotion, learning its attack
+    pub fn promote(&mut self, promotion: &skills::Promotion) {
+        // keep "Cancel" at the bottom of the menu
+        let idx = self.attacks.iter()
+            .position(|a| a.name() == "Cancel")
+            .unwrap_or(self.attacks.len());
+        self.attacks.insert(idx, promotion.attack.clone());
+        self.promotions.push(promotion.name.clone());
+    }
+
+    /// returns the items the unit is carrying
+    pub fn items(&self) -> Vec<Item> {
+        self.items.clone()
+    }
+
+    /// gives the unit an item to carry
+    pub fn add_item(&mut self, item: Item) {
+        self.items.push(item);
+    }
+
+    /// replaces everything the unit is carrying
+    pub fn set_items(&mut self, items: Vec<Item>) {
+        self.items = items;
+    }
+
+    /// takes an item away from the unit
+    pub fn remove_item(&mut self, idx: usize) -> Item {
+        self.items.remove(idx)
+    }
+
+    /// returns the extra damage loaded for the unit's next attack
+    pub fn loaded_damage(&self) -> u64 {
+        self.loaded
+    }
+
+    /// sets the extra damage for the unit's next attack
+    pub fn set_loaded(&mut self, damage: u64) {
+        self.loaded = damage;
+    }
+
+    /// returns the most hp the unit can be healed up to
+    pub fn max_health(&self) -> u64 {
+        self.max_health
+    }
+
+    /// sets the most hp the unit can be healed up to
+    pub fn set_max_health(&mut self, max_health: u64) {
+        self.max_health = max_health;
+        self.health = self.health.min(max_health);
+    }
+
+    /// restores some of the unit's hp, up to its max health
+    pub fn heal(&mut self, amount: u64) {
+        self.health = (self.health + amount).min(self.max_health);
+    }
+
+    /// brings a downed unit back with some hp and no action points until next round
+    pub fn revive(&mut self, health: u64) {
+        self.health = health.min(self.max_health);
+        self.set_points(0, 0);
+        self.buffs.clear();
+        self.overwatch = None;
+    }
+
+    /// returns the attack the unit has ready on overwatch, if it is on overwatch
+    pub fn overwatch(&self) -> Option<Attack> {
+        self.overwatch.clone()
+    }
+
+    /// arms or stands down the unit's reaction shot
+    pub fn set_overwatch(&mut self, attack: Option<Attack>) {
+        self.overwatch = attack;
+    }
+
+    /// returns how many diagonal steps the unit has taken this activation
+    pub fn diagonal_steps(&self) -> u64 {
+        self.diagonal_steps
+    }
+
+    /// updates how many diagonal steps the unit has taken this activation
+    pub fn set_diagonal_steps(&mut self, steps: u64) {
+        self.diagonal_steps = steps;
+    }
+
+    /// returns the unit's support abilities
+    pub fn abilities(&self) -> Vec<Ability> {
+        self.abilities.clone()
+    }
+
+    /// uses up one of an ability's uses
+    pub fn spend_ability(&mut self, idx: usize) {
+        self.abilities[idx].spend_use();
+    }
+
+    /// returns the buffs on the unit, including ones that have run out
+    pub fn buffs(&self) -> Vec<Buff> {
+        self.buffs.clone()
+    }
+
+    /// gives the unit a buff
+    pub fn add_buff(&mut self, buff: Buff) {
+        self.buffs.push(buff);
+    }
+
+    /// returns how much the unit's buffs raise a stat during `round`
+    pub fn buff(&self, kind: BuffKind, round: u64) -> u64 {
+        self.buffs.iter()
+            .filter(|b| b.kind == kind && b.last_round >= round)
+            .map(|b| b.amount)
+            .sum()
+    }
+
     /// deals damage to the unit, returning if its still alive or not
     pub fn deal_damage(&mut self, damage: u64) -> DamageStatus {
+        // banked points soak up some of the hit
+        let soaked = (damage / DEFENSE_PER_POINT).min(self.banked_points);
+        self.banked_points -= soaked;
+        let damage = damage - soaked * DEFENSE_PER_POINT;
+
         // see if the damage would kill us
         if damage >= self.health {
             self.health = 0;
@@ -163,4 +544,43 @@ impl Unit {
     pub fn move_unit(&mut self, newpos: Position) {
         self.position = newpos
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_stats_are_valid() {
+        assert!(UnitStats::default().validate().is_ok());
+    }
+
+    #[test]
+    fn fewer_points_than_an_ability_costs_are_fine() {
+        let stats = UnitStats {
+            action_points: 2,
+            attacks: vec![Attack::new("Fight".to_string(), 50, 1f64, 2)],
+            ..UnitStats::default()
+        };
+        assert!(stats.validate().is_ok());
+    }
+
+    #[test]
+    fn every_attack_has_to_be_affordable() {
+        let stats = UnitStats {
+            attacks: vec![
+                Attack::new("Fight".to_string(), 50, 1f64, 2),
+                Attack::new("Shoot".to_string(), 30, 5f64, 9)
+            ],
+            ..UnitStats::default()
+        };
+        assert!(stats.validate().is_err());
+    }
+
+    #[test]
+    fn units_need_points_and_an_attack() {
+        let no_points = UnitStats { action_points: 0, ..UnitStats::default() };
+        assert!(no_points.validate().is_err());
+        let no_attacks = UnitStats { attacks: Vec::new(), ..UnitStats::default() };
+        assert!(no_attacks.validate().is_err());
+    }
+}
@@ -1,10 +1,28 @@
+use std::convert::TryFrom;
+
 /// defines a movement direction
-#[derive(Copy,Clone,PartialEq)]
+///
+/// which of these a unit can actually take depends on the map's topology
+#[derive(Copy,Clone,PartialEq,Debug)]
 pub enum Movement {
     Up,
     Down,
     Left,
-    Right
+    Right,
+    UpLeft,
+    UpRight,
+    DownLeft,
+    DownRight
+}
+
+impl Movement {
+    /// returns if the direction goes across rows and columns at once
+    pub fn is_diagonal(&self) -> bool {
+        matches!(
+            self,
+            Movement::UpLeft | Movement::UpRight | Movement::DownLeft | Movement::DownRight
+        )
+    }
 }
 
 /// display format implementation
@@ -15,17 +33,40 @@ impl std::fmt::Display for Movement {
             Movement::Down => "Down",
             Movement::Left => "Left",
             Movement::Right => "Right",
+            Movement::UpLeft => "Up-Left",
+            Movement::UpRight => "Up-Right",
+            Movement::DownLeft => "Down-Left",
+            Movement::DownRight => "Down-Right",
         };
         
         write!(f, "{}", printable)
     }
 }
 
+/// parses a movement from its name or first letters, ignoring case
+impl std::str::FromStr for Movement {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "up" | "u" => Ok(Movement::Up),
+            "down" | "d" => Ok(Movement::Down),
+            "left" | "l" => Ok(Movement::Left),
+            "right" | "r" => Ok(Movement::Right),
+            "up-left" | "upleft" | "ul" => Ok(Movement::UpLeft),
+            "up-right" | "upright" | "ur" => Ok(Movement::UpRight),
+            "down-left" | "downleft" | "dl" => Ok(Movement::DownLeft),
+            "down-right" | "downright" | "dr" => Ok(Movement::DownRight),
+            _ => Err(format!("'{}' is not a direction", s))
+        }
+    }
+}
+
 
 
 
 /// defines a position on the board
-#[derive(Copy,Clone,PartialEq)]
+#[derive(Copy,Clone,PartialEq,Debug)]
 pub struct Position {
     x: usize,
     y: usize
@@ -38,6 +79,22 @@ impl std::fmt::Display for Position {
     }
 }
 
+/// parses a position written the same way it is displayed, e.g. `3x4`
+impl std::str::FromStr for Position {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split('x');
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(x), Some(y), None) => match (x.trim().parse(), y.trim().parse()) {
+                (Ok(x), Ok(y)) => Ok(Position::new(x, y)),
+                _ => Err(format!("'{}' is not a position", s))
+            },
+            _ => Err(format!("'{}' is not a position, expected something like 3x4", s))
+        }
+    }
+}
+
 impl Position {
     /// creates a new position
     pub fn new(x:usize, y:usize) -> Self {
@@ -55,38 +112,17 @@ impl Position {
     }
 
     /// updates the position
+    #[allow(dead_code)]
     pub fn update(&mut self, x: usize, y: usize) {
         self.x = x;
         self.y = y;
     }
 
-    /// calculates the distance between two positions
-    pub fn distance(&self, p: Self) -> f64 {
-        let x_max: usize;
-        let x_min: usize;
-        let y_max: usize;
-        let y_min: usize;
-
-        if self.x() > p.x() {
-            x_max = self.x();
-            x_min = p.x();
-        } else {
-            x_max = p.x();
-            x_min = self.x();
-        }
-
-        if self.y() > p.y() {
-            y_max = self.y();
-            y_min = p.y();
-        } else {
-            y_max = p.y();
-            y_min = self.y();
-        }
-        
-        // return the distance 
-        (
-            ((x_max-x_min) as f64).powf(2f64) + // a^2 + ...
-            ((y_max-y_min) as f64).powf(2f64)   // b^2...
-        ).sqrt()                                // = c^2, so c=c.sqrt()
+    /// returns the position `dx` rows and `dy` columns away, or `None` if
+    /// that would go below zero. whether it's on the board is up to the board
+    pub fn offset(&self, dx: i64, dy: i64) -> Option<Self> {
+        let x = usize::try_from(self.x as i64 + dx).ok()?;
+        let y = usize::try_from(self.y as i64 + dy).ok()?;
+        Some(Position::new(x, y))
     }
 }
\ No newline at end of file
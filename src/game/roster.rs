@@ -0,0 +1,255 @@
+// defines a roster of units kept between battles and how it is saved
+
+use super::{
+    Attack,
+    Item,
+    Position,
+    Team,
+    Unit,
+    Units,
+    UnitStats
+};
+
+/// a team's units as they carry over from one battle to the next
+#[derive(Clone)]
+pub struct Roster {
+    team:   Team,
+    units:  Vec<Unit>
+}
+
+impl Roster {
+    /// creates a roster from a team's units, leaving out everyone else
+    pub fn new(team: Team, units: &Units) -> Self {
+        Roster {
+            team,
+            units: units.iter().filter(|u| u.team() == team).cloned().collect()
+        }
+    }
+
+    /// returns the team of the roster
+    pub fn team(&self) -> Team {
+        self.team
+    }
+
+    /// returns the units on the roster
+    pub fn units(&self) -> &[Unit] {
+        &self.units
+    }
+
+    /// returns fresh copies of the roster's units for a new battle, numbered
+    /// from `first_id`. they keep what they have earned and carry, and take
+    /// the rest from the battle's `base` stats
+    pub fn deploy(&self, first_id: u64, base: &UnitStats) -> Vec<Unit> {
+        let mut units = Vec::new();
+        for (idx, unit) in self.units.iter().enumerate() {
+            let stats = UnitStats {
+                health: unit.health(),
+                initiative: unit.initiative(),
+                accuracy: unit.accuracy(),
+                attacks: Self::loadout(unit),
+                ..base.clone()
+            };
+            let mut fresh = Unit::from_stats(
+                unit.name(),
+                first_id + idx as u64,
+                self.team,
+                &stats,
+                Position::new(0, 0)
+            );
+            fresh.set_progress(
+                unit.experience(),
+                unit.accuracy(),
+                unit.range_bonus(),
+                unit.promotions()
+            );
+            fresh.set_max_health(unit.max_health());
+            fresh.set_items(unit.items());
+            units.push(fresh);
+        }
+        units
+    }
+
+    /// returns the attacks that are saved for a unit, leaving out "Cancel"
+    fn loadout(unit: &Unit) -> Vec<Attack> {
+        unit.attacks()
+            .iter()
+            .filter(|a| a.name() != "Cancel")
+            .cloned()
+            .collect()
+    }
+
+    /// writes the roster as lines of text
+    ///
+    /// each unit is one line of
+    /// `unit|<name>|<health>|<initiative>|<attacks>|<xp>|<accuracy>|<range bonus>|<promotions>|<items>|<max health>`,
+    /// with the attacks written as `<name>:<damage>:<range>:<cost>` joined by `;`
+    /// and the promotions and item names joined by `;`. a name with a `|` or
+    /// `;` in it would split up wrong when read back, so it can't be saved
+    pub fn to_lines(&self) -> Result<Vec<String>, String> {
+        let mut lines = vec![format!("team|{}", self.team)];
+        for unit in &self.units {
+            let names = std::iter::once(unit.name())
+                .chain(Self::loadout(unit).into_iter().map(|a| a.name()))
+                .chain(unit.promotions());
+            for name in names {
+                if name.contains(['|', ';']) {
+                    return Err(format!("Can't save '{}' on the roster, names can't have a '|' or ';' in them", name));
+                }
+            }
+            let attacks: Vec<String> = Self::loadout(unit).iter().map(|a| a.to_string()).collect();
+            let items: Vec<String> = unit.items().iter().map(|i| i.name()).collect();
+            lines.push(format!(
+                "unit|{}|{}|{}|{}|{}|{}|{}|{}|{}|{}",
+                unit.name(),
+                unit.health(),
+                unit.initiative(),
+                attacks.join(";"),
+                unit.experience(),
+                unit.accuracy(),
+                unit.range_bonus(),
+                unit.promotions().join(";"),
+                items.join(";"),
+                unit.max_health()
+            ));
+        }
+        Ok(lines)
+    }
+
+    /// reads a roster back from the lines written by `to_lines`
+    pub fn from_lines(lines: &[String]) -> Result<Self, String> {
+        let mut team: Option<Team> = None;
+        let mut units = Vec::new();
+
+        for line in lines {
+            let fields: Vec<&str> = line.split('|').collect();
+            match fields[0] {
+                "team" if fields.len() == 2 => team = Some(fields[1].parse::<Team>()?),
+                // older saves leave off the experience, item and max health fields
+                "unit" if [5, 9, 10, 11].contains(&fields.len()) => {
+                    let team = match team {
+                        Some(t) => t,
+                        None => return Err("Roster lists units before its team".to_string())
+                    };
+                    let health = match fields[2].parse::<u64>() {
+                        Ok(a) => a,
+                        Err(_) => return Err(format!("Bad health in roster line '{}'", line))
+                    };
+                    let initiative = match fields[3].parse::<u64>() {
+                        Ok(a) => a,
+                        Err(_) => return Err(format!("Bad initiative in roster line '{}'", line))
+                    };
+                    let mut attacks = Vec::new();
+                    for attack in fields[4].split(';').filter(|a| !a.is_empty()) {
+                        attacks.push(attack.parse::<Attack>()?);
+                    }
+                    let mut unit = Unit::new(
+                        fields[1].to_string(),
+                        units.len() as u64,
+                        team,
+                        health,
+                        initiative,
+                        attacks,
+                        Position::new(0, 0)
+                    );
+                    if fields.len() >= 9 {
+                        let progress = (
+                            fields[5].parse::<u64>(),
+                            fields[6].parse::<u64>(),
+                            fields[7].parse::<u64>()
+                        );
+                        let promotions = fields[8].split(';')
+                            .filter(|p| !p.is_empty())
+                            .map(|p| p.to_string())
+                            .collect();
+                        match progress {
+                            (Ok(xp), Ok(accuracy), Ok(range)) =>
+                                unit.set_progress(xp, accuracy, range, promotions),
+                            _ => return Err(format!("Bad experience in roster line '{}'", line))
+                        }
+                    }
+                    if fields.len() >= 10 {
+                        for name in fields[9].split(';').filter(|i| !i.is_empty()) {
+                            match Item::by_name(name) {
+                                Some(item) => unit.add_item(item),
+                                None => return Err(format!("Unknown item '{}' in roster line '{}'", name, line))
+                            }
+                        }
+                    }
+                    if fields.len() == 11 {
+                        match fields[10].parse::<u64>() {
+                            Ok(a) => unit.set_max_health(a),
+                            Err(_) => return Err(format!("Bad max health in roster line '{}'", line))
+                        }
+                    }
+                    units.push(unit);
+                },
+                _ => return Err(format!("Bad roster line '{}'", line))
+            }
+        }
+
+        match team {
+            Some(team) => Ok(Roster { team, units }),
+            None => Err("Roster has no team".to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roster_of(name: &str) -> Roster {
+        let mut units = Units::new();
+        units.insert(Unit::from_stats(
+            name.to_string(),
+            0,
+            Team::Bluefor,
+            &UnitStats::default(),
+            Position::new(0, 0)
+        ));
+        Roster::new(Team::Bluefor, &units)
+    }
+
+    fn lines(text: &[&str]) -> Vec<String> {
+        text.iter().map(|l| l.to_string()).collect()
+    }
+
+    #[test]
+    fn reads_back_what_it_writes() {
+        let roster = roster_of("Billy #1");
+        let lines = roster.to_lines().unwrap();
+        let read = Roster::from_lines(&lines).unwrap();
+        assert!(read.team() == Team::Bluefor);
+        assert_eq!(read.to_lines().unwrap(), lines);
+    }
+
+    #[test]
+    fn reads_older_unit_lines() {
+        let read = Roster::from_lines(&lines(&[
+            "team|Bluefor",
+            "unit|Old|80|10|Fight:50:1:2",
+            "unit|Levelled|90|10|Shoot:30:10:3|120|90|1|Marksman",
+            "unit|Carrying|70|10|Shoot:30:10:3|0|85|0||Medkit;Ammo"
+        ])).unwrap();
+        let units = read.units();
+        assert_eq!(units.len(), 3);
+        assert_eq!(units[0].health(), 80);
+        assert_eq!(units[1].experience(), 120);
+        assert_eq!(units[1].promotions(), vec!["Marksman".to_string()]);
+        assert_eq!(units[2].items().len(), 2);
+    }
+
+    #[test]
+    fn rejects_bad_lines() {
+        assert!(Roster::from_lines(&lines(&["unit|Billy|100|10|Fight:50:1:2"])).is_err());
+        assert!(Roster::from_lines(&lines(&["team|Bluefor", "unit|Billy|100|10"])).is_err());
+        assert!(Roster::from_lines(&lines(&["team|Bluefor", "unit|Billy|lots|10|Fight:50:1:2"])).is_err());
+        assert!(Roster::from_lines(&lines(&["team|Bluefor", "unit|Billy|100|10|Fight|0|85|0||"])).is_err());
+    }
+
+    #[test]
+    fn wont_save_names_that_split_the_line() {
+        assert!(roster_of("Billy|Bob").to_lines().is_err());
+        assert!(roster_of("Billy;Bob").to_lines().is_err());
+    }
+}
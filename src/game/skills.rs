@@ -0,0 +1,172 @@
+// defines the skill tree units level up and get promoted through
+
+use super::{
+    Attack,
+    Unit
+};
+
+/// experience it takes to go up a level
+pub const XP_PER_LEVEL: u64 = 100;
+/// bonus experience for finishing off an enemy
+pub const KILL_XP: u64 = 50;
+
+/// the skill tree used when none is loaded, in the same format as a skill tree file
+pub const DEFAULT_SKILL_TREE: &str = "
+# level <level> <health|accuracy|range> <amount>
+level 2 health 20
+level 3 accuracy 10
+level 4 range 1
+level 5 health 20
+level 6 accuracy 10
+
+# promotion <level> <name>: <attack>:<damage>:<range>:<cost> [requires <attack>]
+promotion 2 Marksman: Aimed Shot:45:12:4 requires Shoot
+promotion 2 Brawler: Haymaker:70:1:3 requires Fight
+promotion 4 Grenadier: Grenade:40:5:3
+promotion 4 Sniper: Headshot:80:15:4 requires Aimed Shot
+";
+
+/// a stat that goes up with levels
+#[derive(Clone, Copy, PartialEq)]
+pub enum Stat {
+    Health,
+    Accuracy,
+    Range
+}
+
+/// display format implementation
+impl std::fmt::Display for Stat {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let printable = match self {
+            Stat::Health => "health",
+            Stat::Accuracy => "accuracy",
+            Stat::Range => "range",
+        };
+
+        write!(f, "{}", printable)
+    }
+}
+
+/// a stat bonus handed out when a unit reaches a level
+#[derive(Clone)]
+pub struct LevelBonus {
+    pub level:  u64,
+    pub stat:   Stat,
+    pub amount: u64
+}
+
+/// a promotion a unit can pick once it reaches a level, unlocking an attack
+#[derive(Clone)]
+pub struct Promotion {
+    pub name:       String,
+    pub level:      u64,
+    pub attack:     Attack,
+    pub requires:   Option<String>
+}
+
+/// the level bonuses and promotions available to units
+#[derive(Clone)]
+pub struct SkillTree {
+    bonuses:    Vec<LevelBonus>,
+    promotions: Vec<Promotion>
+}
+
+impl SkillTree {
+    /// returns the built in skill tree
+    pub fn default_tree() -> Self {
+        Self::parse(DEFAULT_SKILL_TREE).expect("the default skill tree should parse")
+    }
+
+    /// parses a skill tree, see `DEFAULT_SKILL_TREE` for the format
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut tree = SkillTree {
+            bonuses: Vec::new(),
+            promotions: Vec::new()
+        };
+
+        for line in text.lines().map(|l| l.trim()) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let words: Vec<&str> = line.split_whitespace().collect();
+            match words[0] {
+                "level" if words.len() == 4 => {
+                    let stat = match words[2] {
+                        "health" => Stat::Health,
+                        "accuracy" => Stat::Accuracy,
+                        "range" => Stat::Range,
+                        _ => return Err(format!("Unknown stat '{}'", words[2]))
+                    };
+                    match (words[1].parse::<u64>(), words[3].parse::<u64>()) {
+                        (Ok(level), Ok(amount)) => tree.bonuses.push(LevelBonus { level, stat, amount }),
+                        _ => return Err(format!("Bad skill tree line '{}'", line))
+                    }
+                },
+                "promotion" if words.len() >= 4 => {
+                    tree.promotions.push(Self::parse_promotion(line)?);
+                },
+                _ => return Err(format!("Bad skill tree line '{}'", line))
+            }
+        }
+
+        Ok(tree)
+    }
+
+    /// parses a `promotion <level> <name>: <attack> [requires <attack>]` line
+    fn parse_promotion(line: &str) -> Result<Promotion, String> {
+        let bad = || format!("Bad skill tree line '{}'", line);
+
+        let rest = line.trim_start_matches("promotion").trim();
+        let (level, rest) = rest.split_once(' ').ok_or_else(bad)?;
+        let level = level.parse::<u64>().map_err(|_| bad())?;
+        let (name, rest) = rest.split_once(':').ok_or_else(bad)?;
+        let (attack, requires) = match rest.split_once(" requires ") {
+            Some((a, r)) => (a.trim(), Some(r.trim().to_string())),
+            None => (rest.trim(), None)
+        };
+
+        // the attack is <name>:<damage>:<range>:<cost>
+        let attack = attack.parse::<Attack>().map_err(|_| bad())?;
+
+        Ok(Promotion {
+            name: name.trim().to_string(),
+            level,
+            attack,
+            requires
+        })
+    }
+
+    /// returns the stat bonuses for reaching `level`
+    pub fn bonuses(&self, level: u64) -> Vec<LevelBonus> {
+        self.bonuses.iter().filter(|b| b.level == level).cloned().collect()
+    }
+
+    /// returns the promotion called `name`
+    pub fn promotion(&self, name: &str) -> Option<&Promotion> {
+        self.promotions.iter().find(|p| p.name == name)
+    }
+
+    /// returns the promotions a unit could pick right now
+    ///
+    /// a unit gets one promotion from each level it has reached, as long as
+    /// it already knows the attack the promotion builds on
+    pub fn available(&self, unit: &Unit) -> Vec<Promotion> {
+        let attacks = unit.attacks();
+        let taken_levels: Vec<u64> = unit.promotions()
+            .iter()
+            .filter_map(|t| self.promotion(t))
+            .map(|p| p.level)
+            .collect();
+
+        self.promotions.iter()
+            .filter(|p| p.level <= unit.level() && !taken_levels.contains(&p.level))
+            .filter(|p| match &p.requires {
+                Some(r) => attacks.iter().any(|a| a.name() == *r),
+                None => true
+            })
+            .filter(|p| !attacks.iter().any(|a| a.name() == p.attack.name()))
+            .cloned()
+            .collect()
+    }
+}
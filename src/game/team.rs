@@ -20,13 +20,26 @@ impl std::fmt::Display for Team {
     }
 }
 
+/// parses a team from its name or first letter, ignoring case
+impl std::str::FromStr for Team {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "redfor" | "red" | "r" => Ok(Team::Redfor),
+            "bluefor" | "blue" | "b" => Ok(Team::Bluefor),
+            _ => Err(format!("'{}' is not a team", s))
+        }
+    }
+}
+
 impl Team {
     /// returns the other team
     pub fn other_team(self) -> Team {
         if self == Team::Redfor {
-            return Team::Bluefor;
+            Team::Bluefor
         } else {
-            return Team::Redfor;
+            Team::Redfor
         }
     }
 }
\ No newline at end of file
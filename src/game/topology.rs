@@ -0,0 +1,395 @@
+// defines the shapes of grid a map can be laid out on
+
+use super::{
+    Movement,
+    Position
+};
+
+/// how the tiles of a map fit together
+///
+/// on a hex map positions are axial coordinates, `x` being the row and `y`
+/// the column, with each row sitting half a tile further right than the one
+/// below it. so the six neighbours of a tile are either side of it in its own
+/// row, the tile above it and the one up and to its left, and the tile below
+/// it and the one down and to its right
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Topology {
+    /// square tiles, moving up, down, left and right, and diagonally if the
+    /// rules allow it
+    Square,
+    /// hexagonal tiles, moving to any of six neighbours
+    Hex
+}
+
+/// how ranges are measured on a square map
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Metric {
+    /// in a straight line
+    Euclidean,
+    /// in steps up, down, left and right
+    Manhattan,
+    /// in steps any way, diagonals included
+    Chebyshev,
+    /// in steps any way, with each diagonal counting as about 1.4
+    Octile
+}
+
+/// which diagonal moves are allowed on a square map
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Diagonals {
+    /// only up, down, left and right
+    Off,
+    /// any diagonal onto an open tile
+    Free,
+    /// not through a gap between two corners of cover
+    NoSqueeze,
+    /// not past any corner of cover
+    NoCorners
+}
+
+/// the directions units can move on a square map
+const SQUARE_DIRECTIONS: [Movement; 4] = [
+    Movement::Up,
+    Movement::Down,
+    Movement::Left,
+    Movement::Right
+];
+
+/// the directions units can move on a square map with diagonals
+const EIGHT_DIRECTIONS: [Movement; 8] = [
+    Movement::Up,
+    Movement::Down,
+    Movement::Left,
+    Movement::Right,
+    Movement::UpLeft,
+    Movement::UpRight,
+    Movement::DownLeft,
+    Movement::DownRight
+];
+
+/// the directions units can move on a hex map
+const HEX_DIRECTIONS: [Movement; 6] = [
+    Movement::UpLeft,
+    Movement::UpRight,
+    Movement::Left,
+    Movement::Right,
+    Movement::DownLeft,
+    Movement::DownRight
+];
+
+impl Topology {
+    /// returns every direction a unit can move in, square maps only going
+    /// diagonally if `diagonals` allows it
+    pub fn directions(&self, diagonals: Diagonals) -> &'static [Movement] {
+        match (self, diagonals) {
+            (Topology::Square, Diagonals::Off) => &SQUARE_DIRECTIONS,
+            (Topology::Square, _) => &EIGHT_DIRECTIONS,
+            (Topology::Hex, _) => &HEX_DIRECTIONS
+        }
+    }
+
+    /// returns how many rows and columns one step in direction `mov` goes,
+    /// or `None` if the tiles don't have a neighbour that way
+    pub fn offset(&self, mov: Movement) -> Option<(i64, i64)> {
+        match (self, mov) {
+            (_, Movement::Left) => Some((0, -1)),
+            (_, Movement::Right) => Some((0, 1)),
+            (Topology::Square, Movement::Up) => Some((1, 0)),
+            (Topology::Square, Movement::Down) => Some((-1, 0)),
+            (Topology::Square, Movement::UpLeft) => Some((1, -1)),
+            (Topology::Square, Movement::UpRight) => Some((1, 1)),
+            (Topology::Square, Movement::DownLeft) => Some((-1, -1)),
+            (Topology::Square, Movement::DownRight) => Some((-1, 1)),
+            (Topology::Hex, Movement::UpLeft) => Some((1, -1)),
+            (Topology::Hex, Movement::UpRight) => Some((1, 0)),
+            (Topology::Hex, Movement::DownLeft) => Some((-1, 0)),
+            (Topology::Hex, Movement::DownRight) => Some((-1, 1)),
+            (Topology::Hex, Movement::Up) | (Topology::Hex, Movement::Down) => None
+        }
+    }
+
+    /// returns how far apart two tiles are, measured by `metric` on a
+    /// square map and in steps on a hex map
+    pub fn distance(&self, metric: Metric, from: Position, to: Position) -> f64 {
+        let dx = to.x() as i64 - from.x() as i64;
+        let dy = to.y() as i64 - from.y() as i64;
+        match self {
+            Topology::Square => metric.distance(dx, dy),
+            Topology::Hex => dx.abs().max(dy.abs()).max((dx + dy).abs()) as f64
+        }
+    }
+
+    /// returns the tiles a line between two tiles passes through, not
+    /// counting the tile it starts on
+    pub fn line(&self, from: Position, to: Position) -> Vec<Position> {
+        match self {
+            Topology::Square => square_line(from, to),
+            Topology::Hex => hex_line(from, to)
+        }
+    }
+}
+
+/// walks the tiles along the line between two squares, Bresenham style
+fn square_line(from: Position, to: Position) -> Vec<Position> {
+    let (mut x, mut y) = (from.x() as i64, from.y() as i64);
+    let (x1, y1) = (to.x() as i64, to.y() as i64);
+    let dx = (x1 - x).abs();
+    let dy = -(y1 - y).abs();
+    let sx = if x < x1 { 1 } else { -1 };
+    let sy = if y < y1 { 1 } else { -1 };
+    let mut err = dx + dy;
+
+    let mut tiles = Vec::new();
+    while (x, y) != (x1, y1) {
+        let e2 = 2 * err;
+        if e2 >= dy {
+            err += dy;
+            x += sx;
+        }
+        if e2 <= dx {
+            err += dx;
+            y += sy;
+        }
+        tiles.push(Position::new(x as usize, y as usize));
+    }
+    tiles
+}
+
+/// walks the hexes along the line between two hexes, rounding points along
+/// it to the nearest hex. the line is nudged a hair to one side so lines
+/// running exactly between two hexes always pick the same one
+fn hex_line(from: Position, to: Position) -> Vec<Position> {
+    let n = Topology::Hex.distance(Metric::Euclidean, from, to) as i64;
+    let (x0, y0) = (from.x() as f64 + 1e-6, from.y() as f64 + 2e-6);
+    let (x1, y1) = (to.x() as f64 + 1e-6, to.y() as f64 + 2e-6);
+
+    (1..=n).map(|i| {
+        let t = i as f64 / n as f64;
+        let x = x0 + (x1 - x0) * t;
+        let y = y0 + (y1 - y0) * t;
+        let z = -x - y;
+
+        // round in cube coordinates, fixing up whichever strayed the most
+        let (mut rx, mut ry, rz) = (x.round(), y.round(), z.round());
+        let (ex, ey, ez) = ((rx - x).abs(), (ry - y).abs(), (rz - z).abs());
+        if ex > ey && ex > ez {
+            rx = -ry - rz;
+        } else if ey > ez {
+            ry = -rx - rz;
+        }
+        Position::new(rx.max(0f64) as usize, ry.max(0f64) as usize)
+    }).collect()
+}
+
+impl Metric {
+    /// returns how far it is to a tile `dx` rows and `dy` columns away
+    pub fn distance(&self, dx: i64, dy: i64) -> f64 {
+        let (dx, dy) = (dx.unsigned_abs() as f64, dy.unsigned_abs() as f64);
+        match self {
+            Metric::Euclidean => (dx * dx + dy * dy).sqrt(),
+            Metric::Manhattan => dx + dy,
+            Metric::Chebyshev => dx.max(dy),
+            Metric::Octile => dx.max(dy) + (2f64.sqrt() - 1f64) * dx.min(dy)
+        }
+    }
+
+    /// returns how many moves the `nth` diagonal step of an activation costs
+    ///
+    /// a diagonal is two steps under manhattan and one under chebyshev.
+    /// euclidean and octile put it at about 1.4, so every other diagonal
+    /// costs two
+    pub fn diagonal_moves(&self, nth: u64) -> u64 {
+        match self {
+            Metric::Manhattan => 2,
+            Metric::Chebyshev => 1,
+            Metric::Euclidean | Metric::Octile => if nth.is_multiple_of(2) { 2 } else { 1 }
+        }
+    }
+
+    /// returns what a diagonal step costs on average, in half moves
+    pub fn diagonal_half_moves(&self) -> u64 {
+        match self {
+            Metric::Manhattan => 4,
+            Metric::Chebyshev => 2,
+            Metric::Euclidean | Metric::Octile => 3
+        }
+    }
+}
+
+/// display format implementation
+impl std::fmt::Display for Metric {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let printable = match self {
+            Metric::Euclidean => "euclidean",
+            Metric::Manhattan => "manhattan",
+            Metric::Chebyshev => "chebyshev",
+            Metric::Octile => "octile"
+        };
+
+        write!(f, "{}", printable)
+    }
+}
+
+/// parses a metric from its name, ignoring case
+impl std::str::FromStr for Metric {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "euclidean" => Ok(Metric::Euclidean),
+            "manhattan" => Ok(Metric::Manhattan),
+            "chebyshev" => Ok(Metric::Chebyshev),
+            "octile" => Ok(Metric::Octile),
+            _ => Err(format!(
+                "'{}' is not a metric, expected euclidean, manhattan, chebyshev or octile",
+                s
+            ))
+        }
+    }
+}
+
+/// display format implementation
+impl std::fmt::Display for Diagonals {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let printable = match self {
+            Diagonals::Off => "off",
+            Diagonals::Free => "free",
+            Diagonals::NoSqueeze => "no-squeeze",
+            Diagonals::NoCorners => "no-corners"
+        };
+
+        write!(f, "{}", printable)
+    }
+}
+
+/// parses a diagonal rule from its name, ignoring case
+impl std::str::FromStr for Diagonals {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "off" => Ok(Diagonals::Off),
+            "free" => Ok(Diagonals::Free),
+            "no-squeeze" => Ok(Diagonals::NoSqueeze),
+            "no-corners" => Ok(Diagonals::NoCorners),
+            _ => Err(format!(
+                "'{}' is not a diagonal rule, expected off, free, no-squeeze or no-corners",
+                s
+            ))
+        }
+    }
+}
+
+/// display format implementation
+impl std::fmt::Display for Topology {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let printable = match self {
+            Topology::Square => "square",
+            Topology::Hex => "hex"
+        };
+
+        write!(f, "{}", printable)
+    }
+}
+
+/// parses a topology from its name, ignoring case
+impl std::str::FromStr for Topology {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "square" => Ok(Topology::Square),
+            "hex" => Ok(Topology::Hex),
+            _ => Err(format!("'{}' is not a grid, expected square or hex", s))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_distance_counts_steps() {
+        let from = Position::new(3, 3);
+        let hex = |x, y| Topology::Hex.distance(Metric::Euclidean, from, Position::new(x, y));
+        assert_eq!(hex(3, 3), 0f64);
+        // up and to the left is one step, but up and to the right is two
+        assert_eq!(hex(4, 2), 1f64);
+        assert_eq!(hex(4, 4), 2f64);
+        assert_eq!(hex(0, 6), 3f64);
+        assert_eq!(hex(6, 6), 6f64);
+    }
+
+    #[test]
+    fn every_hex_direction_is_one_step() {
+        let from = Position::new(3, 3);
+        for mov in Topology::Hex.directions(Diagonals::Off) {
+            let (dx, dy) = Topology::Hex.offset(*mov).unwrap();
+            let to = from.offset(dx, dy).unwrap();
+            assert_eq!(Topology::Hex.distance(Metric::Euclidean, from, to), 1f64);
+        }
+        assert!(Topology::Hex.offset(Movement::Up).is_none());
+        assert!(Topology::Hex.offset(Movement::Down).is_none());
+    }
+
+    #[test]
+    fn hex_lines_step_through_neighbouring_hexes() {
+        let (from, to) = (Position::new(0, 0), Position::new(3, 2));
+        let line = Topology::Hex.line(from, to);
+        assert_eq!(line.len(), 5);
+        assert!(line.last() == Some(&to));
+
+        let mut last = from;
+        for pos in line {
+            assert_eq!(Topology::Hex.distance(Metric::Euclidean, last, pos), 1f64);
+            last = pos;
+        }
+    }
+
+    #[test]
+    fn topologies_parse_their_own_names() {
+        for grid in [Topology::Square, Topology::Hex] {
+            assert_eq!(grid.to_string().to_uppercase().parse::<Topology>(), Ok(grid));
+        }
+        assert!("triangle".parse::<Topology>().is_err());
+    }
+
+    #[test]
+    fn metrics_measure_a_knight_move_differently() {
+        let knight = |metric: Metric| metric.distance(2, -1);
+        assert_eq!(knight(Metric::Euclidean), 5f64.sqrt());
+        assert_eq!(knight(Metric::Manhattan), 3f64);
+        assert_eq!(knight(Metric::Chebyshev), 2f64);
+        assert_eq!(knight(Metric::Octile), 1f64 + 2f64.sqrt());
+    }
+
+    #[test]
+    fn every_other_diagonal_costs_two_under_euclidean_and_octile() {
+        for metric in [Metric::Euclidean, Metric::Octile] {
+            let costs: Vec<u64> = (1..=4).map(|n| metric.diagonal_moves(n)).collect();
+            assert_eq!(costs, vec![1, 2, 1, 2]);
+            assert_eq!(metric.diagonal_half_moves(), 3);
+        }
+        assert_eq!(Metric::Manhattan.diagonal_moves(1), 2);
+        assert_eq!(Metric::Chebyshev.diagonal_moves(2), 1);
+    }
+
+    #[test]
+    fn square_maps_only_go_diagonally_when_allowed() {
+        assert_eq!(Topology::Square.directions(Diagonals::Off).len(), 4);
+        assert_eq!(Topology::Square.directions(Diagonals::NoCorners).len(), 8);
+    }
+
+    #[test]
+    fn metrics_and_diagonal_rules_parse_their_own_names() {
+        for metric in [Metric::Euclidean, Metric::Manhattan, Metric::Chebyshev, Metric::Octile] {
+            assert_eq!(metric.to_string().parse::<Metric>(), Ok(metric));
+        }
+        for rule in [Diagonals::Off, Diagonals::Free, Diagonals::NoSqueeze, Diagonals::NoCorners] {
+            assert_eq!(rule.to_string().parse::<Diagonals>(), Ok(rule));
+        }
+        assert!("taxicab".parse::<Metric>().is_err());
+        assert!("sometimes".parse::<Diagonals>().is_err());
+    }
+}
@@ -0,0 +1,74 @@
+// defines where the units in a battle are kept and how they're looked up
+
+use std::iter::FromIterator;
+
+use super::Unit;
+
+/// the units in a battle, kept in a slot per entity id so looking one up
+/// doesn't mean searching for it
+///
+/// taking a unit out leaves its slot empty rather than shifting everyone
+/// after it down, so ids stay put and a unit can be put back where it was.
+/// units are always gone through in id order
+#[derive(Clone, Default)]
+pub struct Units {
+    slots:  Vec<Option<Unit>>
+}
+
+impl Units {
+    /// creates an empty store
+    pub fn new() -> Self {
+        Units {
+            slots: Vec::new()
+        }
+    }
+
+    /// adds a unit under its id, handing back whoever had that id before
+    pub fn insert(&mut self, unit: Unit) -> Option<Unit> {
+        let id = unit.entity_id() as usize;
+        if id >= self.slots.len() {
+            self.slots.resize(id + 1, None);
+        }
+        self.slots[id].replace(unit)
+    }
+
+    /// takes the unit with `id` out, leaving its slot empty
+    pub fn remove(&mut self, id: u64) -> Option<Unit> {
+        self.slots.get_mut(id as usize)?.take()
+    }
+
+    /// returns the unit with `id`
+    pub fn get(&self, id: u64) -> Option<&Unit> {
+        self.slots.get(id as usize)?.as_ref()
+    }
+
+    /// returns the unit with `id` to change it
+    pub fn get_mut(&mut self, id: u64) -> Option<&mut Unit> {
+        self.slots.get_mut(id as usize)?.as_mut()
+    }
+
+    /// goes through the units in id order
+    pub fn iter(&self) -> impl Iterator<Item = &Unit> + '_ {
+        self.slots.iter().flatten()
+    }
+
+    /// goes through the units in id order to change them
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Unit> + '_ {
+        self.slots.iter_mut().flatten()
+    }
+
+    /// returns the lowest id no unit has had yet
+    pub fn next_id(&self) -> u64 {
+        self.slots.len() as u64
+    }
+}
+
+impl FromIterator<Unit> for Units {
+    fn from_iter<I: IntoIterator<Item = Unit>>(iter: I) -> Self {
+        let mut units = Units::new();
+        for unit in iter {
+            units.insert(unit);
+        }
+        units
+    }
+}
@@ -0,0 +1,392 @@
+// defines the ways a team can win a match
+
+use super::{
+    Board,
+    ControlPoint,
+    Position,
+    Team,
+    Units,
+    DEFAULT_CONTROL_RADIUS
+};
+
+/// a single way for a team to win
+#[derive(Clone)]
+pub enum Condition {
+    /// a team wins once the other team has no units left
+    Elimination,
+    /// after this many rounds the team with the higher score wins
+    TurnLimit {
+        rounds:     u64
+    },
+    /// a team wins by being the only team on every tile for this many rounds in a row
+    HoldTiles {
+        tiles:      Vec<Position>,
+        rounds:     u64
+    },
+    /// the other team wins once this unit is killed
+    KillVip {
+        unit_id:    u64,
+        team:       Team
+    },
+    /// the team wins once one of its units reaches the zone
+    Extraction {
+        team:       Team,
+        zone:       Vec<Position>
+    },
+    /// the first team to score this many points from control points wins
+    ScoreLimit {
+        points:     u64
+    }
+}
+
+/// how a match was decided
+#[derive(Clone)]
+pub enum Outcome {
+    Winner(Team, String),
+    Draw(String)
+}
+
+/// display format implementation
+impl std::fmt::Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Outcome::Winner(team, reason) =>
+                write!(f, "{} WINS! ({})", team.to_string().to_uppercase(), reason),
+            Outcome::Draw(reason) =>
+                write!(f, "DRAW! ({})", reason)
+        }
+    }
+}
+
+/// the set of victory conditions for a match, and how far along each one is
+#[derive(Clone)]
+pub struct Scenario {
+    conditions:     Vec<Condition>,
+    // the team holding each `HoldTiles` condition and for how many rounds
+    held:           Vec<Option<(Team, u64)>>,
+    control:        Vec<ControlPoint>,
+    control_radius: f64,
+    redfor_score:   u64,
+    bluefor_score:  u64,
+    // an outcome decided outside the conditions, e.g. by a mission script
+    declared:       Option<Outcome>
+}
+
+impl Scenario {
+    /// creates a scenario that is won by wiping out the enemy
+    pub fn new() -> Self {
+        Scenario {
+            conditions: vec![Condition::Elimination],
+            held: vec![None],
+            control: Vec::new(),
+            control_radius: DEFAULT_CONTROL_RADIUS,
+            redfor_score: 0,
+            bluefor_score: 0,
+            declared: None
+        }
+    }
+
+    /// adds a control point that scores each round for the team holding it
+    pub fn add_control_point(&mut self, position: Position) {
+        self.control.push(ControlPoint::new(position));
+    }
+
+    /// sets how close units need to be to a control point to capture it
+    pub fn set_control_radius(&mut self, radius: f64) {
+        self.control_radius = radius;
+    }
+
+    /// returns the control points of the scenario
+    pub fn control_points(&self) -> &[ControlPoint] {
+        &self.control
+    }
+
+    /// returns the control point at `pos`, if there is one
+    pub fn control_point(&self, pos: Position) -> Option<&ControlPoint> {
+        self.control.iter().find(|c| c.position() == pos)
+    }
+
+    /// adds a condition to the scenario
+    pub fn add(&mut self, condition: Condition) {
+        self.conditions.push(condition);
+        self.held.push(None);
+    }
+
+    /// ends the match with `outcome` the next time the conditions are checked
+    pub fn declare(&mut self, outcome: Outcome) {
+        self.declared = Some(outcome);
+    }
+
+    /// returns the conditions of the scenario
+    pub fn conditions(&self) -> &[Condition] {
+        &self.conditions
+    }
+
+    /// returns the team holding the condition at `idx` and for how many rounds
+    pub fn held(&self, idx: usize) -> Option<(Team, u64)> {
+        self.held.get(idx).copied().flatten()
+    }
+
+    /// returns a team's score
+    ///
+    /// this is the points earned from control points if there are any, and
+    /// otherwise the total health of the team's units left standing
+    pub fn score(&self, units: &Units, team: Team) -> u64 {
+        if !self.control.is_empty() {
+            return match team {
+                Team::Redfor => self.redfor_score,
+                Team::Bluefor => self.bluefor_score
+            };
+        }
+
+        units.iter()
+            .filter(|u| u.team() == team)
+            .map(|u| u.health())
+            .sum()
+    }
+
+    /// counts up who is holding which tiles at the end of a round
+    pub fn end_round(&mut self, board: &Board, units: &Units) {
+        // capture control points and score the ones held
+        for point in self.control.iter_mut() {
+            point.contest(board, units, self.control_radius);
+            match point.owner() {
+                Some(Team::Redfor) => self.redfor_score += 1,
+                Some(Team::Bluefor) => self.bluefor_score += 1,
+                None => ()
+            }
+        }
+
+        for (idx, cond) in self.conditions.iter().enumerate() {
+            if let Condition::HoldTiles { tiles, .. } = cond {
+                // every tile needs a unit on it, all from the same team
+                let mut holder: Option<Team> = None;
+                let mut contested = false;
+                for tile in tiles {
+                    match units.iter().find(|u| u.position() == *tile) {
+                        Some(u) => match holder {
+                            Some(t) if t != u.team() => contested = true,
+                            _ => holder = Some(u.team())
+                        },
+                        None => contested = true
+                    }
+                }
+
+                self.held[idx] = match (holder, contested, self.held[idx]) {
+                    (Some(team), false, Some((t, n))) if t == team => Some((team, n + 1)),
+                    (Some(team), false, _) => Some((team, 1)),
+                    _ => None
+                };
+            }
+        }
+    }
+
+    /// checks every condition, returning the outcome if the match is over
+    ///
+    /// `round_over` is set once every unit has acted in `round`, which is
+    /// when the round based conditions are checked
+    pub fn evaluate(&self, units: &Units, round: u64, round_over: bool) -> Option<Outcome> {
+        if self.declared.is_some() {
+            return self.declared.clone();
+        }
+
+        for (idx, cond) in self.conditions.iter().enumerate() {
+            match cond {
+                Condition::Elimination => {
+                    let red = units.iter().any(|u| u.team() == Team::Redfor);
+                    let blue = units.iter().any(|u| u.team() == Team::Bluefor);
+                    match (red, blue) {
+                        (false, false) => return Some(Outcome::Draw("no units left".to_string())),
+                        (true, false) => return Some(Outcome::Winner(Team::Redfor, "no more Bluefor units".to_string())),
+                        (false, true) => return Some(Outcome::Winner(Team::Bluefor, "no more Redfor units".to_string())),
+                        (true, true) => ()
+                    }
+                },
+                Condition::TurnLimit { rounds } => {
+                    if round_over && round >= *rounds {
+                        let red = self.score(units, Team::Redfor);
+                        let blue = self.score(units, Team::Bluefor);
+                        let reason = format!("turn limit reached, {} to {}", red.max(blue), red.min(blue));
+                        if red > blue {
+                            return Some(Outcome::Winner(Team::Redfor, reason));
+                        } else if blue > red {
+                            return Some(Outcome::Winner(Team::Bluefor, reason));
+                        }
+                        return Some(Outcome::Draw(format!("turn limit reached, tied at {}", red)));
+                    }
+                },
+                Condition::HoldTiles { rounds, .. } => {
+                    if let Some((team, n)) = self.held[idx] {
+                        if n >= *rounds {
+                            return Some(Outcome::Winner(team, format!("held the objective for {} rounds", n)));
+                        }
+                    }
+                },
+                Condition::KillVip { unit_id, team } => {
+                    if !units.iter().any(|u| u.entity_id() == *unit_id) {
+                        return Some(Outcome::Winner(team.other_team(), format!("the {} VIP was killed", team)));
+                    }
+                },
+                Condition::Extraction { team, zone } => {
+                    if let Some(u) = units.iter().find(|u| u.team() == *team && zone.contains(&u.position())) {
+                        return Some(Outcome::Winner(*team, format!("{} reached the extraction zone", u.name())));
+                    }
+                },
+                Condition::ScoreLimit { points } => {
+                    let red = self.score(units, Team::Redfor);
+                    let blue = self.score(units, Team::Bluefor);
+                    // both can cross the line in the same round, so the higher score takes it
+                    if red >= *points || blue >= *points {
+                        let reason = format!("reached {} points, {} to {}", points, red.max(blue), red.min(blue));
+                        if red > blue {
+                            return Some(Outcome::Winner(Team::Redfor, reason));
+                        } else if blue > red {
+                            return Some(Outcome::Winner(Team::Bluefor, reason));
+                        }
+                        return Some(Outcome::Draw(format!("both teams reached {} points", points)));
+                    }
+                }
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{
+        Unit,
+        UnitStats
+    };
+
+    /// units at full health, one for each team and position given
+    fn units(placed: &[(Team, Position)]) -> Units {
+        let mut units = Units::new();
+        for (id, (team, pos)) in placed.iter().enumerate() {
+            units.insert(Unit::from_stats(format!("Unit #{}", id), id as u64, *team, &UnitStats::default(), *pos));
+        }
+        units
+    }
+
+    /// who won, or `None` for a draw
+    fn winner(outcome: Option<Outcome>) -> Option<Team> {
+        match outcome.expect("the match should be over") {
+            Outcome::Winner(team, _) => Some(team),
+            Outcome::Draw(_) => None
+        }
+    }
+
+    #[test]
+    fn elimination_is_always_on() {
+        let scenario = Scenario::new();
+        let both = units(&[(Team::Redfor, Position::new(0, 0)), (Team::Bluefor, Position::new(5, 5))]);
+        assert!(scenario.evaluate(&both, 1, true).is_none());
+
+        let red = units(&[(Team::Redfor, Position::new(0, 0))]);
+        assert!(winner(scenario.evaluate(&red, 1, false)) == Some(Team::Redfor));
+        assert!(winner(scenario.evaluate(&Units::new(), 1, false)).is_none());
+    }
+
+    #[test]
+    fn turn_limit_waits_for_the_end_of_the_last_round() {
+        let mut scenario = Scenario::new();
+        scenario.add(Condition::TurnLimit { rounds: 2 });
+        let mut both = units(&[(Team::Redfor, Position::new(0, 0)), (Team::Bluefor, Position::new(5, 5))]);
+        assert!(scenario.evaluate(&both, 2, false).is_none());
+        assert!(scenario.evaluate(&both, 1, true).is_none());
+        // level on health is a draw, otherwise the healthier team wins
+        assert!(winner(scenario.evaluate(&both, 2, true)).is_none());
+        both.get_mut(0).unwrap().deal_damage(10);
+        assert!(winner(scenario.evaluate(&both, 2, true)) == Some(Team::Bluefor));
+    }
+
+    #[test]
+    fn holding_tiles_needs_every_tile_for_rounds_in_a_row() {
+        let board = Board::new(&Default::default());
+        let tiles = vec![Position::new(3, 3), Position::new(3, 4)];
+        let mut scenario = Scenario::new();
+        scenario.add(Condition::HoldTiles { tiles, rounds: 2 });
+
+        let one = units(&[(Team::Redfor, Position::new(3, 3)), (Team::Bluefor, Position::new(9, 9))]);
+        scenario.end_round(&board, &one);
+        assert!(scenario.held(1).is_none());
+
+        let both = units(&[
+            (Team::Redfor, Position::new(3, 3)),
+            (Team::Redfor, Position::new(3, 4)),
+            (Team::Bluefor, Position::new(9, 9))
+        ]);
+        scenario.end_round(&board, &both);
+        assert!(scenario.held(1) == Some((Team::Redfor, 1)));
+        assert!(scenario.evaluate(&both, 1, true).is_none());
+        scenario.end_round(&board, &both);
+        assert!(winner(scenario.evaluate(&both, 2, true)) == Some(Team::Redfor));
+    }
+
+    #[test]
+    fn contested_tiles_reset_the_count() {
+        let board = Board::new(&Default::default());
+        let mut scenario = Scenario::new();
+        scenario.add(Condition::HoldTiles { tiles: vec![Position::new(3, 3), Position::new(3, 4)], rounds: 2 });
+
+        let red = units(&[(Team::Redfor, Position::new(3, 3)), (Team::Redfor, Position::new(3, 4))]);
+        scenario.end_round(&board, &red);
+        let mixed = units(&[(Team::Redfor, Position::new(3, 3)), (Team::Bluefor, Position::new(3, 4))]);
+        scenario.end_round(&board, &mixed);
+        assert!(scenario.held(1).is_none());
+        scenario.end_round(&board, &red);
+        assert!(scenario.held(1) == Some((Team::Redfor, 1)));
+    }
+
+    #[test]
+    fn losing_the_vip_loses_the_match() {
+        let mut scenario = Scenario::new();
+        scenario.add(Condition::KillVip { unit_id: 0, team: Team::Bluefor });
+        let mut placed = units(&[
+            (Team::Bluefor, Position::new(0, 0)),
+            (Team::Bluefor, Position::new(0, 1)),
+            (Team::Redfor, Position::new(5, 5))
+        ]);
+        assert!(scenario.evaluate(&placed, 1, false).is_none());
+        placed.remove(0);
+        assert!(winner(scenario.evaluate(&placed, 1, false)) == Some(Team::Redfor));
+    }
+
+    #[test]
+    fn reaching_the_zone_extracts() {
+        let mut scenario = Scenario::new();
+        scenario.add(Condition::Extraction { team: Team::Bluefor, zone: vec![Position::new(0, 0)] });
+        // the other team standing in the zone doesn't count
+        let red = units(&[(Team::Redfor, Position::new(0, 0)), (Team::Bluefor, Position::new(5, 5))]);
+        assert!(scenario.evaluate(&red, 1, false).is_none());
+        let blue = units(&[(Team::Redfor, Position::new(1, 1)), (Team::Bluefor, Position::new(0, 0))]);
+        assert!(winner(scenario.evaluate(&blue, 1, false)) == Some(Team::Bluefor));
+    }
+
+    #[test]
+    fn a_declared_outcome_wins_over_everything() {
+        let mut scenario = Scenario::new();
+        scenario.declare(Outcome::Draw("called off".to_string()));
+        let red = units(&[(Team::Redfor, Position::new(0, 0))]);
+        assert!(winner(scenario.evaluate(&red, 1, false)).is_none());
+    }
+
+    #[test]
+    fn control_points_score_each_round_up_to_the_limit() {
+        let board = Board::new(&Default::default());
+        let mut scenario = Scenario::new();
+        scenario.add_control_point(Position::new(5, 5));
+        scenario.add(Condition::ScoreLimit { points: 2 });
+        let placed = units(&[(Team::Redfor, Position::new(5, 5)), (Team::Bluefor, Position::new(12, 12))]);
+
+        // control points replace health as the score
+        assert_eq!(scenario.score(&placed, Team::Bluefor), 0);
+        scenario.end_round(&board, &placed);
+        assert_eq!(scenario.score(&placed, Team::Redfor), 1);
+        assert!(scenario.evaluate(&placed, 1, true).is_none());
+        scenario.end_round(&board, &placed);
+        assert!(winner(scenario.evaluate(&placed, 2, true)) == Some(Team::Redfor));
+    }
+}
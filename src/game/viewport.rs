@@ -0,0 +1,149 @@
+// defines how much of the board fits on screen at once
+
+use super::{
+    Board,
+    Movement,
+    Position
+};
+
+/// the most rows of the board drawn at once
+pub const VIEW_ROWS: usize = 16;
+/// the most columns of the board drawn at once
+pub const VIEW_COLS: usize = 32;
+/// the most rows and columns the minimap squeezes the board into
+const MINIMAP_ROWS: usize = 12;
+const MINIMAP_COLS: usize = 32;
+
+/// the window of the board that gets drawn, as close to centred on a tile
+/// as the edges of the board allow
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Viewport {
+    top:        usize,
+    left:       usize,
+    rows:       usize,
+    cols:       usize
+}
+
+impl Viewport {
+    /// creates the window around `center`, the whole board if it fits
+    pub fn centered(board: &Board, center: Position) -> Self {
+        let rows = board.rows().min(VIEW_ROWS);
+        let cols = board.cols().min(VIEW_COLS);
+        let top = center.x().saturating_sub(rows / 2).min(board.rows() - rows);
+        let left = center.y().saturating_sub(cols / 2).min(board.cols() - cols);
+        Viewport { top, left, rows, cols }
+    }
+
+    /// returns the lowest row in the window
+    pub fn top(&self) -> usize {
+        self.top
+    }
+
+    /// returns the leftmost column in the window
+    pub fn left(&self) -> usize {
+        self.left
+    }
+
+    /// returns how many rows the window has
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// returns how many columns the window has
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// returns the tile in the middle of the window
+    pub fn center(&self) -> Position {
+        Position::new(self.top + self.rows / 2, self.left + self.cols / 2)
+    }
+
+    /// returns if the window shows the whole board
+    pub fn shows_all(&self, board: &Board) -> bool {
+        self.rows == board.rows() && self.cols == board.cols()
+    }
+
+    /// returns the middle of the window moved `count` tiles in direction
+    /// `mov` across the screen, kept on the board
+    pub fn scrolled(&self, board: &Board, mov: Movement, count: usize) -> Position {
+        let (dx, dy) = screen_offset(mov);
+        let center = self.center();
+        let count = count.min(board.rows().max(board.cols())) as i64;
+        let x = (center.x() as i64 + dx * count).clamp(0, board.rows() as i64 - 1);
+        let y = (center.y() as i64 + dy * count).clamp(0, board.cols() as i64 - 1);
+        Position::new(x as usize, y as usize)
+    }
+
+    /// returns how far the window moves by default, half of it in the
+    /// direction of `mov`
+    pub fn page(&self, mov: Movement) -> usize {
+        match screen_offset(mov) {
+            (0, _) => (self.cols / 2).max(1),
+            _ => (self.rows / 2).max(1)
+        }
+    }
+}
+
+/// returns which way the rows and columns go when scrolling the screen in
+/// direction `mov`, whatever the shape of the tiles
+fn screen_offset(mov: Movement) -> (i64, i64) {
+    match mov {
+        Movement::Up => (1, 0),
+        Movement::Down => (-1, 0),
+        Movement::Left => (0, -1),
+        Movement::Right => (0, 1),
+        Movement::UpLeft => (1, -1),
+        Movement::UpRight => (1, 1),
+        Movement::DownLeft => (-1, -1),
+        Movement::DownRight => (-1, 1)
+    }
+}
+
+/// the whole board squeezed down so each cell stands for a square block of tiles
+pub struct Minimap {
+    block:  usize,
+    rows:   usize,
+    cols:   usize
+}
+
+impl Minimap {
+    /// works out how big the blocks need to be for the board to fit
+    pub fn new(board: &Board) -> Self {
+        let block = board.rows().div_ceil(MINIMAP_ROWS)
+            .max(board.cols().div_ceil(MINIMAP_COLS))
+            .max(1);
+        Minimap {
+            block,
+            rows: board.rows().div_ceil(block),
+            cols: board.cols().div_ceil(block)
+        }
+    }
+
+    /// returns how many tiles across each block is
+    pub fn block(&self) -> usize {
+        self.block
+    }
+
+    /// returns how many rows of blocks there are
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// returns how many columns of blocks there are
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// returns the row and column of the block a tile falls in
+    pub fn cell(&self, pos: Position) -> (usize, usize) {
+        (pos.x() / self.block, pos.y() / self.block)
+    }
+
+    /// returns if any of the block at `row`,`col` is in the window
+    pub fn in_view(&self, view: &Viewport, row: usize, col: usize) -> bool {
+        let (x, y) = (row * self.block, col * self.block);
+        x < view.top + view.rows && view.top < x + self.block
+            && y < view.left + view.cols && view.left < y + self.block
+    }
+}
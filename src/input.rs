@@ -0,0 +1,63 @@
+// defines where player input comes from, either the terminal or a script file
+
+use std::collections::VecDeque;
+use std::io::Write;
+
+/// a source of lines of player input
+pub enum InputSource {
+    Stdin,
+    Script {
+        lines:  VecDeque<String>,
+        echo:   bool
+    }
+}
+
+impl InputSource {
+    /// creates an input source that reads from the terminal
+    pub fn stdin() -> Self {
+        InputSource::Stdin
+    }
+
+    /// creates an input source that feeds lines from a script file
+    pub fn from_script(path: &str, echo: bool) -> Result<Self, String> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(a) => a,
+            Err(e) => return Err(format!("Failed to read script '{}': {}", path, e))
+        };
+
+        // skip blank lines and comments
+        let lines = contents
+            .lines()
+            .map(|l| l.trim().to_string())
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .collect();
+
+        Ok(InputSource::Script { lines, echo })
+    }
+
+    /// prints the prompt and reads the next line, returning `None` once input runs out
+    pub fn read(&mut self, prompt: &str) -> Option<String> {
+        match self {
+            InputSource::Stdin => {
+                print!("{}", prompt);
+                std::io::stdout().flush().ok()?;
+                let mut ustr = String::new();
+                match std::io::stdin().read_line(&mut ustr) {
+                    Ok(0) | Err(_) => {
+                        // finish the prompt's line so the next output starts cleanly
+                        println!();
+                        None
+                    },
+                    Ok(_) => Some(ustr.replace("\n", "").replace("\r", ""))
+                }
+            },
+            InputSource::Script { lines, echo } => {
+                let line = lines.pop_front()?;
+                if *echo {
+                    println!("{}{}", prompt, line);
+                }
+                Some(line)
+            }
+        }
+    }
+}
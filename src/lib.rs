@@ -0,0 +1,25 @@
+// include our units stuff
+mod game;
+mod battle;
+pub mod ai;
+mod command;
+mod input;
+mod campaign;
+mod console;
+mod event_log;
+mod mission;
+mod after_action;
+mod builder;
+pub mod config;
+mod cli;
+pub use game::{
+    DeployMode,
+    Diagonals,
+    MapSettings,
+    Metric,
+    Outcome,
+    Team,
+    Topology
+};
+pub use battle::Game;
+pub use cli::run;
@@ -0,0 +1,425 @@
+// runs mission scripts, which set up a battle and react to what happens in it
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use rhai::{
+    Array,
+    CallFnOptions,
+    Dynamic,
+    Engine,
+    EvalAltResult,
+    Map,
+    Scope,
+    AST
+};
+
+use crate::{
+    game,
+    Game
+};
+
+/// how much work a script gets to do each time it runs before it's stopped,
+/// so a runaway loop can't hang the game
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+/// a change a script asked for, made once the script returns
+enum Order {
+    Message(String),
+    Spawn(Box<game::Unit>),
+    Terrain(game::Position, game::Terrain),
+    Item(game::Position, game::Item),
+    Declare(game::Outcome)
+}
+
+/// what a script can see of the game, along with the changes it has asked for
+///
+/// the game lends its board and units for as long as a script runs rather
+/// than having them copied, even on the biggest maps. the script's own spawns
+/// and terrain are kept on top of them, so it sees those before the game does
+struct World {
+    /// the game's board and units while a script runs, and empty stand-ins
+    /// the rest of the time
+    board:      game::Board,
+    units:      game::Units,
+    round:      u64,
+    next_id:    u64,
+    /// what spawned units start with
+    stats:      game::UnitStats,
+    /// terrain the script has changed, latest last
+    terrain:    Vec<(game::Position, game::Terrain)>,
+    /// units the script has spawned
+    spawned:    Vec<game::Unit>,
+    orders:     Vec<Order>
+}
+
+impl World {
+    /// creates a world with nothing lent to it yet
+    fn new() -> Self {
+        World {
+            board: game::Board::new(&game::MapSettings::default()),
+            units: game::Units::new(),
+            round: 0,
+            next_id: 0,
+            stats: game::UnitStats::default(),
+            terrain: Vec::new(),
+            spawned: Vec::new(),
+            orders: Vec::new()
+        }
+    }
+
+    /// borrows the game's board and units for a script to look at, leaving
+    /// the game with the stand-ins until they're given back
+    fn lend(&mut self, g: &mut Game) {
+        std::mem::swap(&mut self.board, &mut g.board);
+        std::mem::swap(&mut self.units, &mut g.units);
+        self.round = g.round;
+        self.next_id = self.units.next_id();
+        self.stats = g.stats.clone();
+    }
+
+    /// hands the board and units back to the game, forgetting the script's
+    /// changes on top of them, which are left to its orders
+    fn give_back(&mut self, g: &mut Game) {
+        std::mem::swap(&mut self.board, &mut g.board);
+        std::mem::swap(&mut self.units, &mut g.units);
+        self.terrain.clear();
+        self.spawned.clear();
+    }
+
+    /// returns the terrain of a tile, as the script has left it
+    fn terrain(&self, pos: game::Position) -> Option<game::Terrain> {
+        match self.terrain.iter().rev().find(|(p, _)| *p == pos) {
+            Some((_, terrain)) => Some(*terrain),
+            None => self.board.tile(pos).map(|t| t.terrain())
+        }
+    }
+
+    /// returns if a unit is standing on a tile, spawned ones included
+    fn occupied(&self, pos: game::Position) -> bool {
+        self.board.occupant(pos).is_some() || self.spawned.iter().any(|u| u.position() == pos)
+    }
+
+    /// returns if a unit could be put on a tile
+    fn is_open(&self, pos: game::Position) -> bool {
+        self.terrain(pos).is_some_and(|t| t.passable()) && !self.occupied(pos)
+    }
+
+    /// returns every unit in the battle, spawned ones included
+    fn units(&self) -> impl Iterator<Item = &game::Unit> {
+        self.units.iter().chain(self.spawned.iter())
+    }
+
+    /// returns the tile at `x`,`y`, failing if it's off the map
+    fn tile(&self, x: i64, y: i64) -> Result<game::Position, Box<EvalAltResult>> {
+        let pos = game::Position::new(x.max(0) as usize, y.max(0) as usize);
+        if x < 0 || y < 0 || !self.board.contains(pos) {
+            return Err(format!("{}x{} is off the map", x, y).into());
+        }
+        Ok(pos)
+    }
+}
+
+/// collects the game's events until the mission gets to run
+struct Inbox(Rc<RefCell<Vec<game::Event>>>);
+
+impl game::Subscriber for Inbox {
+    fn notify(&mut self, event: &game::Event) {
+        self.0.borrow_mut().push(event.clone());
+    }
+}
+
+/// a loaded mission script
+pub struct Mission {
+    path:   String,
+    engine: Engine,
+    ast:    AST,
+    world:  Rc<RefCell<World>>,
+    inbox:  Rc<RefCell<Vec<game::Event>>>
+}
+
+/// turns a tile into a script map with `x` and `y`
+fn position_map(pos: game::Position) -> Dynamic {
+    let mut map = Map::new();
+    map.insert("x".into(), Dynamic::from(pos.x() as i64));
+    map.insert("y".into(), Dynamic::from(pos.y() as i64));
+    map.into()
+}
+
+/// turns a unit into a script map
+fn unit_map(unit: &game::Unit) -> Dynamic {
+    let mut map = Map::new();
+    map.insert("id".into(), Dynamic::from(unit.entity_id() as i64));
+    map.insert("name".into(), Dynamic::from(unit.name()));
+    map.insert("team".into(), Dynamic::from(unit.team().to_string()));
+    map.insert("health".into(), Dynamic::from(unit.health() as i64));
+    map.insert("max_health".into(), Dynamic::from(unit.max_health() as i64));
+    map.insert("level".into(), Dynamic::from(unit.level() as i64));
+    map.insert("position".into(), position_map(unit.position()));
+    map.into()
+}
+
+/// returns the hook that handles an event and what it's called with, or
+/// `None` for events scripts don't hear about
+fn hook(event: &game::Event) -> Option<(&'static str, Map)> {
+    let mut map = Map::new();
+    let name = match event {
+        game::Event::TurnStarted { unit_id, round, .. } => {
+            map.insert("unit".into(), Dynamic::from(*unit_id as i64));
+            map.insert("round".into(), Dynamic::from(*round as i64));
+            "on_turn_started"
+        },
+        game::Event::UnitMoved { unit_id, from, to, .. } => {
+            map.insert("unit".into(), Dynamic::from(*unit_id as i64));
+            map.insert("from".into(), position_map(*from));
+            map.insert("to".into(), position_map(*to));
+            "on_unit_moved"
+        },
+        game::Event::AttackResolved { attacker_id, target_id, attack, damage, result, .. } => {
+            map.insert("attacker".into(), Dynamic::from(*attacker_id as i64));
+            map.insert("target".into(), Dynamic::from(*target_id as i64));
+            map.insert("attack".into(), Dynamic::from(attack.clone()));
+            map.insert("damage".into(), Dynamic::from(*damage as i64));
+            map.insert("hit".into(), Dynamic::from(!matches!(result, game::AttackResult::Missed)));
+            "on_attack"
+        },
+        game::Event::UnitDied { unit_id, name, team, position } => {
+            map.insert("unit".into(), Dynamic::from(*unit_id as i64));
+            map.insert("name".into(), Dynamic::from(name.clone()));
+            map.insert("team".into(), Dynamic::from(team.to_string()));
+            map.insert("position".into(), position_map(*position));
+            "on_unit_died"
+        },
+        game::Event::UnitSpawned { unit_id, .. } => {
+            map.insert("unit".into(), Dynamic::from(*unit_id as i64));
+            "on_unit_spawned"
+        },
+        game::Event::RoundEnded { round } => {
+            map.insert("round".into(), Dynamic::from(*round as i64));
+            "on_round_ended"
+        },
+        _ => return None
+    };
+    Some((name, map))
+}
+
+/// parses a team name given to a script function
+fn script_team(team: &str) -> Result<game::Team, Box<EvalAltResult>> {
+    match team.parse::<game::Team>() {
+        Ok(a) => Ok(a),
+        Err(e) => Err(e.into())
+    }
+}
+
+/// builds the engine scripts run in, with the functions they can call
+fn build_engine(world: &Rc<RefCell<World>>) -> Engine {
+    let mut engine = Engine::new();
+
+    // scripts only get at the game through the functions below
+    engine.set_module_resolver(rhai::module_resolvers::DummyModuleResolver::new());
+    engine.set_max_operations(MAX_OPERATIONS);
+    // debug builds of rhai default to shallower limits, keep scripts working in both
+    engine.set_max_expr_depths(64, 32);
+
+    let w = world.clone();
+    engine.register_fn("message", move |text: &str| {
+        w.borrow_mut().orders.push(Order::Message(text.to_string()));
+    });
+
+    let w = world.clone();
+    engine.register_fn("round", move || w.borrow().round as i64);
+
+    let w = world.clone();
+    engine.register_fn("map_size", move || w.borrow().board.rows() as i64);
+
+    let w = world.clone();
+    engine.register_fn("map_height", move || w.borrow().board.rows() as i64);
+
+    let w = world.clone();
+    engine.register_fn("map_width", move || w.borrow().board.cols() as i64);
+
+    let w = world.clone();
+    engine.register_fn("units", move || -> Array {
+        w.borrow().units().map(unit_map).collect()
+    });
+
+    let w = world.clone();
+    engine.register_fn("unit", move |id: i64| -> Dynamic {
+        if id < 0 {
+            return Dynamic::UNIT;
+        }
+        match w.borrow().units().find(|u| u.entity_id() == id as u64) {
+            Some(u) => unit_map(u),
+            None => Dynamic::UNIT
+        }
+    });
+
+    let w = world.clone();
+    engine.register_fn("is_terrain", move |x: i64, y: i64| -> Result<bool, Box<EvalAltResult>> {
+        let world = w.borrow();
+        let pos = world.tile(x, y)?;
+        Ok(world.terrain(pos) == Some(game::Terrain::Wall))
+    });
+
+    let w = world.clone();
+    engine.register_fn("is_empty", move |x: i64, y: i64| -> Result<bool, Box<EvalAltResult>> {
+        let world = w.borrow();
+        let pos = world.tile(x, y)?;
+        Ok(world.is_open(pos))
+    });
+
+    let w = world.clone();
+    engine.register_fn("set_terrain", move |x: i64, y: i64, blocked: bool| -> Result<(), Box<EvalAltResult>> {
+        let mut world = w.borrow_mut();
+        let pos = world.tile(x, y)?;
+        if blocked && world.occupied(pos) {
+            return Err(format!("a unit is standing on {}", pos).into());
+        }
+        let terrain = if blocked { game::Terrain::Wall } else { game::Terrain::Open };
+        world.terrain.push((pos, terrain));
+        world.orders.push(Order::Terrain(pos, terrain));
+        Ok(())
+    });
+
+    let w = world.clone();
+    engine.register_fn("place_item", move |name: &str, x: i64, y: i64| -> Result<(), Box<EvalAltResult>> {
+        let mut world = w.borrow_mut();
+        let pos = world.tile(x, y)?;
+        let item = match game::Item::by_name(name) {
+            Some(a) => a,
+            None => return Err(format!("there's no item called '{}'", name).into())
+        };
+        if !world.terrain(pos).is_some_and(|t| t.passable()) {
+            return Err(format!("{} is blocked by terrain", pos).into());
+        }
+        world.orders.push(Order::Item(pos, item));
+        Ok(())
+    });
+
+    let w = world.clone();
+    engine.register_fn("spawn_unit", move |team: &str, name: &str, x: i64, y: i64| -> Result<i64, Box<EvalAltResult>> {
+        let team = script_team(team)?;
+        let mut world = w.borrow_mut();
+        let pos = world.tile(x, y)?;
+        if !world.is_open(pos) {
+            return Err(format!("{} isn't empty", pos).into());
+        }
+
+        let id = world.next_id;
+        let unit = game::Unit::from_stats(name.to_string(), id, team, &world.stats, pos);
+        world.next_id += 1;
+        world.spawned.push(unit.clone());
+        world.orders.push(Order::Spawn(Box::new(unit)));
+        Ok(id as i64)
+    });
+
+    let w = world.clone();
+    engine.register_fn("win", move |team: &str, reason: &str| -> Result<(), Box<EvalAltResult>> {
+        let team = script_team(team)?;
+        w.borrow_mut().orders.push(Order::Declare(game::Outcome::Winner(team, reason.to_string())));
+        Ok(())
+    });
+
+    let w = world.clone();
+    engine.register_fn("draw", move |reason: &str| {
+        w.borrow_mut().orders.push(Order::Declare(game::Outcome::Draw(reason.to_string())));
+    });
+
+    engine
+}
+
+impl Mission {
+    /// loads the mission script at `path` into the game and runs its setup,
+    /// the statements outside of any function
+    pub fn load(path: &str, g: &mut Game) -> Result<(), String> {
+        let source = match std::fs::read_to_string(path) {
+            Ok(a) => a,
+            Err(e) => return Err(format!("Failed to read mission '{}': {}", path, e))
+        };
+
+        let world = Rc::new(RefCell::new(World::new()));
+        let engine = build_engine(&world);
+        let ast = match engine.compile(&source) {
+            Ok(a) => a,
+            Err(e) => return Err(format!("Failed to load mission '{}': {}", path, e))
+        };
+
+        let inbox = Rc::new(RefCell::new(Vec::new()));
+        g.events.subscribe(Box::new(Inbox(inbox.clone())));
+
+        let mission = Mission {
+            path: path.to_string(),
+            engine,
+            ast,
+            world,
+            inbox
+        };
+        mission.world.borrow_mut().lend(g);
+        let result = mission.engine.run_ast_with_scope(&mut Scope::new(), &mission.ast);
+        mission.world.borrow_mut().give_back(g);
+        if let Err(e) = result {
+            return Err(format!("Mission '{}' failed to set up: {}", path, e));
+        }
+        mission.apply(g);
+
+        // the battle starts from here, the setup isn't something to undo
+        g.history = game::History::new();
+        g.mission = Some(mission);
+        Ok(())
+    }
+
+    /// runs the script's hooks for everything that has happened since it last ran
+    pub fn run(&mut self, g: &mut Game) {
+        loop {
+            let events: Vec<game::Event> = self.inbox.borrow_mut().drain(..).collect();
+            if events.is_empty() {
+                return;
+            }
+
+            for event in events {
+                let (name, args) = match hook(&event) {
+                    Some(a) => a,
+                    None => continue
+                };
+                if !self.ast.iter_functions().any(|f| f.name == name && f.params.len() == 1) {
+                    continue;
+                }
+
+                self.world.borrow_mut().lend(g);
+                let options = CallFnOptions::new().eval_ast(false);
+                let result = self.engine.call_fn_with_options::<Dynamic>(
+                    options,
+                    &mut Scope::new(),
+                    &self.ast,
+                    name,
+                    (args,)
+                );
+                self.world.borrow_mut().give_back(g);
+                match result {
+                    Ok(_) => self.apply(g),
+                    Err(e) => {
+                        // a broken hook shouldn't take the battle down with it
+                        self.world.borrow_mut().orders.clear();
+                        g.events.emit(game::Event::Message {
+                            text: format!("Mission '{}' failed in {}: {}", self.path, name, e)
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    /// makes the changes the script asked for
+    fn apply(&self, g: &mut Game) {
+        let orders: Vec<Order> = self.world.borrow_mut().orders.drain(..).collect();
+        for order in orders {
+            match order {
+                Order::Message(text) => g.events.emit(game::Event::Message { text }),
+                Order::Spawn(unit) => g.spawn_unit(*unit),
+                Order::Terrain(pos, terrain) => g.set_terrain(pos, terrain),
+                Order::Item(pos, item) => g.place_item(pos, item),
+                Order::Declare(outcome) => g.declare_outcome(outcome)
+            }
+        }
+    }
+}
@@ -0,0 +1,110 @@
+// regression scenarios, each playing a short match from a script on a small
+// open map and checking what the game printed and reported
+
+use std::path::PathBuf;
+use std::process::Command;
+
+/// a script and the report it writes, in a directory of their own
+struct Scenario {
+    dir: PathBuf
+}
+
+impl Scenario {
+    fn new(name: &str, script: &[&str]) -> Self {
+        let dir = std::env::temp_dir().join(format!("crap_tactica_{}_{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("script.txt"), script.join("\n") + "\n").unwrap();
+        Scenario { dir }
+    }
+
+    /// plays the script with one unit a side, redfor starting at 0x0 and
+    /// bluefor at 5x3, and gives back what was printed and the report
+    fn play(&self) -> (String, String) {
+        let output = Command::new(env!("CARGO_BIN_EXE_crap_tactica"))
+            .arg("--script").arg(self.dir.join("script.txt"))
+            .arg("--report").arg(self.dir.join("report.txt"))
+            .args(["--seed", "1", "--width", "4", "--height", "6", "--cover", "0", "--units", "1"])
+            .args(["--accuracy", "100", "--fixed-initiative"])
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "the game failed: {}", String::from_utf8_lossy(&output.stderr));
+
+        let report = std::fs::read_to_string(self.dir.join("report.txt")).unwrap();
+        (String::from_utf8(output.stdout).unwrap(), report)
+    }
+}
+
+impl Drop for Scenario {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+#[test]
+fn out_of_input_ends_the_game_unfinished() {
+    let (out, report) = Scenario::new("eof", &[]).play();
+    assert!(out.contains("[-] Out of input, ending the game"));
+    assert!(report.contains("winner: none\nreason: unfinished\nrounds: 1\nturns: 0\n"));
+}
+
+#[test]
+fn move_walks_tile_by_tile() {
+    let (out, report) = Scenario::new("move", &["move up 2"]).play();
+    assert!(out.contains("Billy but Bad #1 moved from 0x0 to 1x0"));
+    assert!(out.contains("Billy but Bad #1 moved from 1x0 to 2x0"));
+    assert!(report.contains("[Redfor] Billy but Bad #1 (lvl 1) - 100 hp at 2x0"));
+}
+
+#[test]
+fn attack_hits_the_target() {
+    let (out, report) = Scenario::new("attack", &["attack shoot 1"]).play();
+    assert!(out.contains("[+] Attack hit! Billy #1 is now at 70 hp!"));
+    assert!(report.contains("[Bluefor] Billy #1 (lvl 1) - 70 hp at 5x3"));
+}
+
+#[test]
+fn undo_takes_back_a_move_and_an_attack() {
+    let (out, report) = Scenario::new("undo", &["move up 1", "attack shoot 1", "undo", "undo"]).play();
+    assert_eq!(out.matches("[+] Last action undone!").count(), 2);
+    assert!(report.contains("[Bluefor] Billy #1 (lvl 1) - 100 hp at 5x3"));
+    assert!(report.contains("[Redfor] Billy but Bad #1 (lvl 1) - 100 hp at 0x0"));
+}
+
+#[test]
+fn redo_puts_back_what_was_undone() {
+    let (out, report) = Scenario::new("redo", &["move up 1", "attack shoot 1", "undo", "undo", "redo", "redo"]).play();
+    assert_eq!(out.matches("[+] Action redone!").count(), 2);
+    assert!(report.contains("[Bluefor] Billy #1 (lvl 1) - 70 hp at 5x3"));
+    assert!(report.contains("[Redfor] Billy but Bad #1 (lvl 1) - 100 hp at 1x0"));
+}
+
+#[test]
+fn round_rolls_over_once_everyone_has_acted() {
+    let (out, report) = Scenario::new("rollover", &["end", "end", "order"]).play();
+    assert!(out.contains("Initiative order for round 2:"));
+    assert!(out.contains("\t1. [Redfor] Billy but Bad #1 (lvl 1) - 100 hp (initiative 10)\n"));
+    assert!(report.contains("rounds: 2\nturns: 2\n"));
+}
+
+#[test]
+fn undo_goes_back_across_the_start_of_a_round() {
+    let (out, report) = Scenario::new("undo_round", &["end", "end", "undo", "order"]).play();
+    assert!(out.contains("[+] Last action undone!"));
+    assert_eq!(last_round(&out), Some(1));
+    assert!(report.contains("rounds: 1\n"));
+}
+
+#[test]
+fn undo_then_redo_of_an_end_stays_in_the_same_round() {
+    let (out, report) = Scenario::new("redo_end", &["end", "undo", "redo", "order"]).play();
+    assert_eq!(last_round(&out), Some(1));
+    assert!(out.contains("\t1. [Redfor] Billy but Bad #1 (lvl 1) - 100 hp (initiative 10) (done)\n"));
+    assert!(report.contains("rounds: 1\n"));
+    assert_eq!(out.matches("round 1, [Redfor] Billy but Bad #1: nothing").count(), 1);
+}
+
+/// the round the last initiative order printed was for
+fn last_round(out: &str) -> Option<u64> {
+    let (i, m) = out.rmatch_indices("Initiative order for round ").next()?;
+    out[i + m.len()..].split(':').next()?.parse().ok()
+}